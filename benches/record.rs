@@ -0,0 +1,74 @@
+//! Compares writing datasets through `Datapoint::record`, which allocates a string for every field,
+//! with the buffer-based `Datapoint::write_record` used by arrays, tuples and derived datapoints.
+//!
+//! Run with `cargo bench --bench record`.
+
+use std::hint::black_box;
+use std::io;
+use std::time::{Duration, Instant};
+
+use delfi::{Datapoint, Dataset, Field};
+
+const ROWS: usize = 200_000;
+const RUNS: usize = 5;
+
+/// A datapoint only implementing `record`, so it is written using the compatibility default
+struct Recorded<const N: usize, T>([T; N]);
+
+impl<const N: usize, T: ToString> Datapoint<N> for Recorded<N, T> {
+    fn record(&self) -> [String; N] {
+        self.0.each_ref().map(T::to_string)
+    }
+}
+
+/// Time the fastest of a number of runs of writing the dataset to a sink
+fn time<const N: usize, D: Datapoint<N>>(dataset: &Dataset<N, D>) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            dataset.write_to(black_box(io::sink())).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn compare<const N: usize, T: Copy + ToString + Field>(name: &str, rows: &[[T; N]]) {
+    let recorded = Dataset::from_datapoints(rows.iter().map(|row| Recorded(*row)));
+    let buffered = Dataset::from_datapoints(rows.iter().copied());
+
+    let recorded = time(&recorded);
+    let buffered = time(&buffered);
+    println!(
+        "{name:<12} record: {recorded:>10.2?}   write_record: {buffered:>10.2?}   speedup: {:.1}x",
+        recorded.as_secs_f64() / buffered.as_secs_f64()
+    );
+}
+
+fn main() {
+    let floats: Vec<[f64; 8]> = (0..ROWS)
+        .map(|i| {
+            #[allow(clippy::cast_precision_loss)]
+            let t = i as f64 * 1e-3;
+            [
+                t,
+                t.sin(),
+                t.cos(),
+                t.exp(),
+                t.sqrt(),
+                t * t,
+                1.0 / (1.0 + t),
+                -t,
+            ]
+        })
+        .collect();
+    compare("f64 x 8", &floats);
+
+    let integers: Vec<[i64; 8]> = (0..ROWS)
+        .map(|i| {
+            let i = i64::try_from(i).unwrap_or_default();
+            [i, -i, i * i, i * 7919, i % 13, i << 20, i / 3, i64::MAX - i]
+        })
+        .collect();
+    compare("i64 x 8", &integers);
+}
@@ -0,0 +1,222 @@
+//! How the fields of a struct or the variants of an enum are recorded as columns
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+
+use crate::fields::{self, Column, Fields};
+use crate::generics;
+
+/// The labels of one or more columns
+pub(crate) enum Segment {
+    /// A single column
+    Label(String),
+    /// The columns of a nested datapoint, with each label prefixed
+    Nested { prefix: String, ty: syn::Type },
+}
+
+/// The columns recorded for a type, along with the body of its `write_record` implementation
+pub(crate) struct Recording {
+    pub segments: Vec<Segment>,
+    /// Writes the fields of `self` into `record`
+    pub body: TokenStream,
+    /// The types of all fields recorded using the `Field` (or `Display`) trait
+    pub types: Vec<syn::Type>,
+}
+
+impl Recording {
+    /**
+    A struct is recorded with one column per field (that is not skipped),
+    or one column per element for flattened fields
+    */
+    pub(crate) fn for_struct(fields: &syn::Fields, generics: &syn::Generics) -> syn::Result<Self> {
+        let fields = Fields::parse(fields)?;
+        let mut segments = Vec::new();
+        let mut body = TokenStream::new();
+        let mut types = Vec::new();
+        for column in fields.columns {
+            let Column {
+                member,
+                label,
+                ty,
+                flatten,
+                ..
+            } = &column;
+            match (flatten, ty) {
+                (false, _) => {
+                    body.extend(column.push(&quote! { &self.#member }));
+                    if column.render.is_none() {
+                        types.push(ty.clone());
+                    }
+                    segments.push(Segment::Label(label.clone()));
+                }
+                // Arrays are expanded here, which requires their length to be known
+                (true, syn::Type::Array(array)) => {
+                    let len = array_len(array)?;
+                    segments.extend((0..len).map(|i| Segment::Label(format!("{label}.{i}"))));
+                    let push = fields::dispatch(&quote! { element });
+                    body.extend(quote! {
+                        for element in &self.#member {
+                            #push
+                        }
+                    });
+                    types.push((*array.elem).clone());
+                }
+                // The number of columns of a nested datapoint must be known without its generic parameters
+                (true, _) if generics::uses_type_params(ty, generics) => {
+                    let message = format!(
+                        "field `{}` can not be flattened, as its type depends on generic parameters",
+                        quote! { #member },
+                    );
+                    return Err(syn::Error::new_spanned(ty, message));
+                }
+                (true, _) => {
+                    body.extend(quote! { delfi::Nested::write_nested(&self.#member, record); });
+                    segments.push(Segment::Nested {
+                        prefix: format!("{label}."),
+                        ty: ty.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Self {
+            segments,
+            body,
+            types,
+        })
+    }
+
+    /**
+    An enum is recorded with a tag column holding the name of the variant, followed by the union
+    of the fields of all variants (matched by label), writing fields absent from a variant as missing values
+    */
+    pub(crate) fn for_enum(name: &syn::Ident, data: &syn::DataEnum) -> syn::Result<Self> {
+        let mut variants = Vec::new();
+        let mut labels = vec![name.to_string()];
+        let mut types = Vec::new();
+        for variant in &data.variants {
+            let tag = fields::variant_tag(variant)?;
+            let fields = Fields::parse(&variant.fields)?;
+            for column in &fields.columns {
+                if column.flatten {
+                    let message = "fields of enum variants can not be flattened";
+                    return Err(syn::Error::new(column.ty.span(), message));
+                }
+                if !labels[1..].contains(&column.label) {
+                    labels.push(column.label.clone());
+                }
+                if column.render.is_none() {
+                    types.push(column.ty.clone());
+                }
+            }
+            variants.push((variant, tag, fields));
+        }
+
+        let arms = variants.iter().map(|(variant, tag, fields)| {
+            let ident = &variant.ident;
+            let bindings: Vec<_> = (0..fields.columns.len())
+                .map(|i| format_ident!("field_{}", i))
+                .collect();
+            let members = fields.columns.iter().map(|c| &c.member);
+            let pushes = labels[1..].iter().map(|label| {
+                match fields.columns.iter().position(|c| &c.label == label) {
+                    Some(i) => {
+                        let binding = &bindings[i];
+                        fields.columns[i].push(&quote! { #binding })
+                    }
+                    None => quote! { record.push_missing(); },
+                }
+            });
+            quote! {
+                Self::#ident { #(#members: #bindings,)* .. } => {
+                    record.push_str(#tag);
+                    #(#pushes)*
+                }
+            }
+        });
+
+        let body = if data.variants.is_empty() {
+            quote! { match *self {} }
+        } else {
+            quote! { match self { #(#arms)* } }
+        };
+        Ok(Self {
+            segments: labels.into_iter().map(Segment::Label).collect(),
+            body,
+            types,
+        })
+    }
+
+    /// The number of columns, as an expression if it depends on nested datapoints
+    pub(crate) fn cols(&self) -> TokenStream {
+        let labels = self
+            .segments
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Label(_)))
+            .count();
+        let nested: Vec<_> = self
+            .segments
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Label(_) => None,
+                Segment::Nested { ty, .. } => Some(ty),
+            })
+            .collect();
+        if nested.is_empty() {
+            quote! { #labels }
+        } else {
+            quote! { { #labels #(+ <#nested as delfi::Nested>::WIDTH)* } }
+        }
+    }
+
+    /// The labels of all columns, computed at compile time if there are nested datapoints
+    pub(crate) fn labels(&self, cols: &TokenStream) -> TokenStream {
+        let mut items = TokenStream::new();
+        let mut segments = Vec::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Label(label) => segments.push(quote! { &[#label] }),
+                Segment::Nested { prefix, ty } => {
+                    let bytes = format_ident!("BYTES_{}", i);
+                    let labels = format_ident!("LABELS_{}", i);
+                    items.extend(quote! {
+                        const #bytes: [u8; delfi::nested::prefixed_len(#prefix, <#ty as delfi::Nested>::NESTED_LABELS)] =
+                            delfi::nested::prefixed_bytes(#prefix, <#ty as delfi::Nested>::NESTED_LABELS);
+                        const #labels: [&str; <#ty as delfi::Nested>::WIDTH] =
+                            delfi::nested::split_prefixed(&#bytes, #prefix, <#ty as delfi::Nested>::NESTED_LABELS);
+                    });
+                    segments.push(quote! { &#labels });
+                }
+            }
+        }
+
+        if items.is_empty() {
+            let labels = self.segments.iter().map(|segment| match segment {
+                Segment::Label(label) => label,
+                Segment::Nested { .. } => unreachable!("there are no nested datapoints"),
+            });
+            quote! { [#(#labels),*] }
+        } else {
+            quote! {
+                {
+                    #items
+                    delfi::nested::join::<#cols>(&[#(#segments),*])
+                }
+            }
+        }
+    }
+}
+
+/// The length of an array type, which must be an integer literal
+fn array_len(array: &syn::TypeArray) -> syn::Result<usize> {
+    match &array.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => len.base10_parse(),
+        len => {
+            let message = "flattened arrays must have an integer literal as length";
+            Err(syn::Error::new(len.span(), message))
+        }
+    }
+}
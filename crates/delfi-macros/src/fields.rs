@@ -0,0 +1,230 @@
+//! The columns of a struct, as given by its fields and their `#[delfi(...)]` attributes
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+
+/// A field which is recorded as a column
+pub(crate) struct Column {
+    /// Used to access the field, i.e. `self.#member`
+    pub member: syn::Member,
+    /// The label of the column: The renamed label, field name or field index
+    pub label: String,
+    pub ty: syn::Type,
+    /// Whether the field is expanded into one column per element, with the label used as prefix
+    pub flatten: bool,
+    /// How the field is written, if not using the `Field` (or `Display`) trait
+    pub render: Option<Render>,
+}
+
+impl Column {
+    /// Write the field into `record`, given an expression for a reference to it
+    pub(crate) fn push(&self, value: &TokenStream) -> TokenStream {
+        match &self.render {
+            None => dispatch(value),
+            Some(Render::Format(format)) => {
+                quote! { record.push_display(&::core::format_args!(#format, #value)); }
+            }
+            Some(Render::With(path)) => quote! { record.push(&#path(#value)); },
+        }
+    }
+}
+
+/**
+Write a value into `record`, given an expression for a reference to it,
+using its `Field` implementation if it has one and its `Display` implementation otherwise
+*/
+pub(crate) fn dispatch(value: &TokenStream) -> TokenStream {
+    quote! {
+        {
+            use delfi::field::dispatch::{ViaDisplay as _, ViaField as _};
+            (&delfi::field::dispatch::Dispatch(#value)).write_dispatch(record);
+        }
+    }
+}
+
+/// How a field is written in place of its `Field` implementation
+pub(crate) enum Render {
+    /// A format string with a single argument, such as `"{:.6e}"`
+    Format(syn::LitStr),
+    /// A function taking a reference to the field and returning a `Field`, such as a `String`
+    With(syn::Path),
+}
+
+/// The fields of a struct which are recorded as columns, in column order
+pub(crate) struct Fields {
+    pub columns: Vec<Column>,
+}
+
+impl Fields {
+    /// Sort the fields into columns, using the attributes of each field
+    pub(crate) fn parse(fields: &syn::Fields) -> syn::Result<Self> {
+        let mut ordered = Vec::new();
+        let mut unordered = Vec::new();
+
+        for (index, field) in fields.iter().enumerate() {
+            let attributes = Attributes::parse(field)?;
+            if attributes.skip {
+                continue;
+            }
+            let member = match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(syn::Index::from(index)),
+            };
+            let label = match (attributes.rename, &field.ident) {
+                (Some(rename), _) => rename,
+                (None, Some(ident)) => ident.to_string(),
+                (None, None) => index.to_string(),
+            };
+            let column = Column {
+                member,
+                label,
+                ty: field.ty.clone(),
+                flatten: attributes.flatten,
+                render: attributes.render,
+            };
+            match attributes.order {
+                Some(order) => ordered.push((order, column)),
+                None => unordered.push(column),
+            }
+        }
+
+        // Ordered fields are placed at their given column, the rest fill the remaining columns in order
+        let cols = ordered.len() + unordered.len();
+        let mut slots: Vec<Option<Column>> = (0..cols).map(|_| None).collect();
+        for (order, column) in ordered {
+            let Some(slot) = slots.get_mut(order.value) else {
+                let message = format!("order must be less than the number of columns ({cols})");
+                return Err(syn::Error::new_spanned(&order.literal, message));
+            };
+            if slot.is_some() {
+                let message = format!("another field already has order {}", order.value);
+                return Err(syn::Error::new_spanned(&order.literal, message));
+            }
+            *slot = Some(column);
+        }
+        let mut unordered = unordered.into_iter();
+        let columns = slots
+            .into_iter()
+            .map(|slot| slot.or_else(|| unordered.next()))
+            .collect::<Option<_>>()
+            .expect("there is exactly one field for each column");
+
+        Ok(Self { columns })
+    }
+}
+
+/// The value written to the tag column for an enum variant: The renamed value or the variant name
+pub(crate) fn variant_tag(variant: &syn::Variant) -> syn::Result<String> {
+    let attributes = Attributes::from_attrs(&variant.attrs)?;
+    let configured = attributes.order.is_some() || attributes.render.is_some();
+    if attributes.skip || configured || attributes.flatten {
+        let message = "variants can only be renamed, using `rename = \"...\"`";
+        return Err(syn::Error::new_spanned(variant, message));
+    }
+    Ok(attributes
+        .rename
+        .unwrap_or_else(|| variant.ident.to_string()))
+}
+
+/// The column order given by a field attribute, along with where it was given
+struct Order {
+    value: usize,
+    literal: syn::LitInt,
+}
+
+/// The `#[delfi(...)]` attributes of a single field
+#[derive(Default)]
+struct Attributes {
+    rename: Option<String>,
+    skip: bool,
+    order: Option<Order>,
+    flatten: bool,
+    render: Option<Render>,
+}
+
+impl Attributes {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let attributes = Self::from_attrs(&field.attrs)?;
+        let configured = attributes.rename.is_some() || attributes.order.is_some();
+        if attributes.skip && (configured || attributes.flatten || attributes.render.is_some()) {
+            let message = "a skipped field can not be renamed, ordered, flattened or formatted";
+            return Err(syn::Error::new_spanned(field, message));
+        }
+        if attributes.flatten && attributes.render.is_some() {
+            let message =
+                "a flattened field can not be formatted, as it is written by its elements";
+            return Err(syn::Error::new_spanned(field, message));
+        }
+        Ok(attributes)
+    }
+
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut attributes = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("delfi")) {
+            let syn::Meta::List(list) = attr.parse_meta()? else {
+                return Err(syn::Error::new_spanned(attr, "expected #[delfi(...)]"));
+            };
+            for nested in &list.nested {
+                attributes.parse_nested(nested)?;
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn parse_nested(&mut self, nested: &syn::NestedMeta) -> syn::Result<()> {
+        match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("skip") => {
+                self.skip = true;
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("flatten") => {
+                self.flatten = true;
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) if pair.path.is_ident("rename") => {
+                let syn::Lit::Str(label) = &pair.lit else {
+                    return Err(syn::Error::new(pair.lit.span(), "expected a string"));
+                };
+                self.rename = Some(label.value());
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) if pair.path.is_ident("order") => {
+                let syn::Lit::Int(order) = &pair.lit else {
+                    return Err(syn::Error::new(pair.lit.span(), "expected an integer"));
+                };
+                self.order = Some(Order {
+                    value: order.base10_parse()?,
+                    literal: order.clone(),
+                });
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) if pair.path.is_ident("format") => {
+                let syn::Lit::Str(format) = &pair.lit else {
+                    return Err(syn::Error::new(pair.lit.span(), "expected a format string"));
+                };
+                self.set_render(Render::Format(format.clone()), pair)?;
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(pair)) if pair.path.is_ident("with") => {
+                let syn::Lit::Str(path) = &pair.lit else {
+                    return Err(syn::Error::new(
+                        pair.lit.span(),
+                        "expected a path to a function",
+                    ));
+                };
+                self.set_render(Render::With(path.parse()?), pair)?;
+            }
+            _ => {
+                let message =
+                    "unknown attribute, expected `rename = \"...\"`, `skip`, `order = N`, \
+                               `flatten`, `format = \"...\"` or `with = \"...\"`";
+                return Err(syn::Error::new(nested.span(), message));
+            }
+        }
+        Ok(())
+    }
+    fn set_render(&mut self, render: Render, pair: &syn::MetaNameValue) -> syn::Result<()> {
+        if self.render.is_some() {
+            let message = "a field can only be given one of `format` or `with`";
+            return Err(syn::Error::new_spanned(pair, message));
+        }
+        self.render = Some(render);
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Bounds on the generic parameters of a struct, added to the generated impls
+
+use proc_macro::TokenStream;
+use quote::ToTokens;
+
+/// Check whether a type mentions any of the type parameters of the struct, such as `T` or `Vec<T>`
+pub(crate) fn uses_type_params(ty: &syn::Type, generics: &syn::Generics) -> bool {
+    let params: Vec<_> = generics.type_params().map(|param| &param.ident).collect();
+    if params.is_empty() {
+        return false;
+    }
+    let tokens = TokenStream::from(ty.to_token_stream());
+    contains_ident(tokens, &params)
+}
+
+/// Recursively search a token stream for any of the given identifiers
+fn contains_ident(tokens: TokenStream, idents: &[&syn::Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro::TokenTree::Ident(ident) => {
+            let ident = ident.to_string();
+            idents.iter().any(|param| *param == &ident)
+        }
+        proc_macro::TokenTree::Group(group) => contains_ident(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// Add the given predicates to the where-clause of the generics
+pub(crate) fn with_predicates(
+    generics: &syn::Generics,
+    predicates: impl IntoIterator<Item = syn::WherePredicate>,
+) -> syn::Generics {
+    let mut generics = generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
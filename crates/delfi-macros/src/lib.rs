@@ -1,47 +1,183 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
 
-#[proc_macro_derive(Datapoint)]
+mod datapoint;
+mod fields;
+mod generics;
+
+use datapoint::Recording;
+use fields::Fields;
+
+#[proc_macro_derive(Datapoint, attributes(delfi))]
 pub fn derive_datapoint(input: TokenStream) -> TokenStream {
     let ast = syn::parse_macro_input!(input as syn::DeriveInput);
-    // println!("{:#?}", ast);
+    expand_datapoint(&ast)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+#[proc_macro_derive(FromRecord, attributes(delfi))]
+pub fn derive_from_record(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    expand_from_record(&ast)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
+}
+
+/// Check that a struct has fields, since a unit struct has no columns
+fn require_fields(ast: &syn::DeriveInput, fields: &syn::Fields, derive: &str) -> syn::Result<()> {
+    match fields {
+        syn::Fields::Unit => {
+            let message =
+                format!("{derive} can not be derived for unit structs, as they have no fields");
+            Err(syn::Error::new(ast.ident.span(), message))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn expand_datapoint(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &ast.ident;
 
-    let expanded = match &ast.data {
+    let recording = match &ast.data {
         syn::Data::Struct(s) => {
-            match &s.fields {
-                syn::Fields::Named(fields) => {
-                    let list = &fields.named;
-                    let cols: usize = list.len();
-                    let names: Vec<_> = list.iter().map(|e| e.ident.clone().unwrap()).collect();
-
-                    quote! {
-                        impl delfi::Datapoint<#cols> for #name {
-                            fn record(&self) -> [String; #cols] {
-                                [#(self.#names.to_string()),*]
-                            }
-                        }
-                    }
-                }
-                syn::Fields::Unnamed(fields) => {
-                    let list = &fields.unnamed;
-                    let cols: usize = list.len();
-                    let numbers: Vec<_> = list.iter().enumerate().map(|(i, _)| syn::Index::from(i)).collect();
-
-                    quote! {
-                        impl delfi::Datapoint<#cols> for #name {
-                            fn record(&self) -> [String; #cols] {
-                                [#(self.#numbers.to_string()),*]
-                            }
-                        }
-                    }
-                }
+            require_fields(ast, &s.fields, "Datapoint")?;
+            Recording::for_struct(&s.fields, &ast.generics)?
+        }
+        syn::Data::Enum(e) => Recording::for_enum(name, e)?,
+        syn::Data::Union(u) => {
+            let message = "Datapoint can not be derived for unions";
+            return Err(syn::Error::new(u.union_token.span(), message));
+        }
+    };
+    // Skipped fields are not counted, while flattened fields count their number of elements
+    let cols = recording.cols();
+    let labels = recording.labels(&cols);
+    let Recording { body, types, .. } = recording;
+
+    // Every generic field type must be writable as a field
+    let bounds = types
+        .iter()
+        .filter(|ty| generics::uses_type_params(ty, &ast.generics))
+        .map(|ty| -> syn::WherePredicate { syn::parse_quote!(#ty: delfi::Field) });
+    let generics = generics::with_predicates(&ast.generics, bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics delfi::Datapoint<#cols> for #name #ty_generics #where_clause {
+            fn record(&self) -> [String; #cols] {
+                delfi::field::record(self)
+            }
+
+            fn write_record(&self, record: &mut delfi::RecordBuf) {
+                #body
+            }
+        }
+
+        impl #impl_generics delfi::Labelled<#cols> for #name #ty_generics #where_clause {
+            const LABELS: [&'static str; #cols] = #labels;
+        }
+
+        impl #impl_generics delfi::Nested for #name #ty_generics #where_clause {
+            const WIDTH: usize = #cols;
+            const NESTED_LABELS: &'static [&'static str] = &<Self as delfi::Labelled<#cols>>::LABELS;
 
-                syn::Fields::Unit => panic!("Cannot derive Datapoint for unit struct"),
+            fn write_nested(&self, record: &mut delfi::RecordBuf) {
+                delfi::Datapoint::<#cols>::write_record(self, record);
             }
         }
-        _ => unimplemented!(),
+
+        impl #impl_generics delfi::DynDatapoint for #name #ty_generics #where_clause {
+            fn record_dyn(&self) -> Vec<String> {
+                delfi::Datapoint::<#cols>::record(self).into()
+            }
+
+            fn write_dyn(&self, _labels: Option<&[String]>, record: &mut delfi::RecordBuf) {
+                delfi::Datapoint::<#cols>::write_record(self, record);
+            }
+
+            fn width(&self) -> usize {
+                #cols
+            }
+        }
+    })
+}
+
+fn expand_from_record(ast: &syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &ast.ident;
+
+    let s = match &ast.data {
+        syn::Data::Struct(s) => s,
+        syn::Data::Enum(e) => {
+            let message = "FromRecord can not be derived for enums";
+            return Err(syn::Error::new(e.enum_token.span(), message));
+        }
+        syn::Data::Union(u) => {
+            let message = "FromRecord can not be derived for unions";
+            return Err(syn::Error::new(u.union_token.span(), message));
+        }
     };
+    require_fields(ast, &s.fields, "FromRecord")?;
+    let fields = Fields::parse(&s.fields)?;
+    if let Some(column) = fields.columns.iter().find(|c| c.flatten) {
+        let message = "flattened fields are not supported when deriving FromRecord";
+        return Err(syn::Error::new(column.ty.span(), message));
+    }
+    let cols: usize = fields.columns.len();
+    let vars: Vec<_> = (0..cols).map(|i| format_ident!("field_{}", i)).collect();
 
-    TokenStream::from(expanded)
+    // Every generic field type must be parsable, or have a default value if skipped
+    let mut bounds: Vec<syn::WherePredicate> = Vec::new();
+
+    // Each field is parsed from its own column, reporting the column label on failure,
+    // while skipped fields are given their default value
+    let values: Vec<_> = s.fields.iter().enumerate().map(|(index, field)| {
+        let member = match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(syn::Index::from(index)),
+        };
+        let column = fields.columns.iter().position(|c| c.member == member);
+        let ty = &field.ty;
+        if generics::uses_type_params(ty, &ast.generics) {
+            match column {
+                Some(_) => bounds.extend([
+                    syn::parse_quote!(#ty: ::core::str::FromStr),
+                    syn::parse_quote!(<#ty as ::core::str::FromStr>::Err: ::core::fmt::Display),
+                ]),
+                None => bounds.push(syn::parse_quote!(#ty: ::core::default::Default)),
+            }
+        }
+        match column {
+            Some(column) => {
+                let var = &vars[column];
+                let label = &fields.columns[column].label;
+                quote! {
+                    #var.parse().map_err(|e| {
+                        delfi::from_record::ParseError::new(#column, &#var, e).with_field(#label)
+                    })?
+                }
+            }
+            None => quote! { ::core::default::Default::default() },
+        }
+    }).collect();
+    let generics = generics::with_predicates(&ast.generics, bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let construct = match &s.fields {
+        syn::Fields::Named(named) => {
+            let names = named.named.iter().map(|e| &e.ident);
+            quote! { Self { #(#names: #values),* } }
+        }
+        _ => quote! { Self(#(#values),*) },
+    };
+
+    Ok(quote! {
+        impl #impl_generics delfi::FromRecord<#cols> for #name #ty_generics #where_clause {
+            fn from_record(record: [String; #cols]) -> Result<Self, delfi::from_record::ParseError> {
+                let [#(#vars),*] = record;
+                Ok(#construct)
+            }
+        }
+    })
 }
@@ -0,0 +1,122 @@
+/*!
+Implementations on the [`Columns`] trait, used to construct datasets from columns of different types
+*/
+
+use crate::field::Field;
+use crate::Datapoint;
+use crate::Error;
+
+/**
+A set of columns which can be zipped together into datapoints, one element from each column.
+This is implemented for tuples of up to 16 columns, where each column can be iterated over
+and may have a different type of element. The resulting datapoints are tuples.
+
+```
+use delfi::columns::Columns;
+
+let steps = vec![0_usize, 1, 2];
+let phases = ["solid", "liquid", "gas"];
+let datapoints = (steps, phases).into_datapoints();
+assert_eq!(datapoints[1], (1, "liquid"));
+```
+*/
+pub trait Columns<const COLS: usize> {
+    /// The datapoint created from one element of each column
+    type Datapoint: Datapoint<COLS>;
+
+    /**
+    Zip the columns into datapoints, stopping as soon as any of the columns run dry
+    */
+    fn into_datapoints(self) -> Vec<Self::Datapoint>;
+
+    /**
+    Zip the columns into datapoints, requiring all columns to be of equal length
+
+    # Errors
+    Returns an error containing the length of each column if the columns differ in length
+    */
+    fn try_into_datapoints(self) -> Result<Vec<Self::Datapoint>, Error>;
+}
+
+/// Implement `Columns` for a tuple, given its arity and the type parameter, index and element name of each column
+macro_rules! impl_columns_for_tuple {
+    ($n:literal => $($name:ident $index:tt $element:ident),+) => {
+        impl<$($name),+> Columns<$n> for ($($name,)+)
+        where
+            $($name: IntoIterator, $name::Item: Field,)+
+        {
+            type Datapoint = ($($name::Item,)+);
+
+            fn into_datapoints(self) -> Vec<Self::Datapoint> {
+                let mut columns = ($(self.$index.into_iter(),)+);
+                let mut data = Vec::new();
+                loop {
+                    $(let Some($element) = columns.$index.next() else { break };)+
+                    data.push(($($element,)+));
+                }
+                data
+            }
+
+            fn try_into_datapoints(self) -> Result<Vec<Self::Datapoint>, Error> {
+                let mut columns = ($(self.$index.into_iter(),)+);
+                let mut data = Vec::new();
+                loop {
+                    let row = ($(columns.$index.next(),)+);
+                    let present = [$(row.$index.is_some()),+];
+                    if !present.contains(&true) {
+                        break;
+                    }
+                    if let ($(Some($element),)+) = row {
+                        data.push(($($element,)+));
+                    } else {
+                        // Count what remains of each column to report the full lengths
+                        let n_datapoints = data.len();
+                        let lengths = vec![$(n_datapoints + usize::from(present[$index]) + columns.$index.count()),+];
+                        return Err(Error::ColumnLengths { lengths });
+                    }
+                }
+                Ok(data)
+            }
+        }
+    };
+}
+
+impl_columns_for_tuple!(1 => C1 0 c1);
+impl_columns_for_tuple!(2 => C1 0 c1, C2 1 c2);
+impl_columns_for_tuple!(3 => C1 0 c1, C2 1 c2, C3 2 c3);
+impl_columns_for_tuple!(4 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4);
+impl_columns_for_tuple!(5 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5);
+impl_columns_for_tuple!(6 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6);
+impl_columns_for_tuple!(7 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7);
+impl_columns_for_tuple!(8 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8);
+impl_columns_for_tuple!(9 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9);
+impl_columns_for_tuple!(10 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10);
+impl_columns_for_tuple!(11 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11);
+impl_columns_for_tuple!(12 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11, C12 11 c12);
+impl_columns_for_tuple!(13 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11, C12 11 c12, C13 12 c13);
+impl_columns_for_tuple!(14 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11, C12 11 c12, C13 12 c13, C14 13 c14);
+impl_columns_for_tuple!(15 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11, C12 11 c12, C13 12 c13, C14 13 c14, C15 14 c15);
+impl_columns_for_tuple!(16 => C1 0 c1, C2 1 c2, C3 2 c3, C4 3 c4, C5 4 c5, C6 5 c6, C7 6 c7, C8 7 c8, C9 8 c9, C10 9 c10, C11 10 c11, C12 11 c12, C13 12 c13, C14 13 c14, C15 14 c15, C16 15 c16);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortest() {
+        let datapoints = (vec![1, 2, 3], ['a', 'b'], ["x", "y", "z"]).into_datapoints();
+        assert_eq!(datapoints, [(1, 'a', "x"), (2, 'b', "y")]);
+    }
+
+    #[test]
+    fn strict() {
+        let datapoints = (vec![1, 2], [0.5, 1.5]).try_into_datapoints().unwrap();
+        assert_eq!(datapoints, [(1, 0.5), (2, 1.5)]);
+
+        let result = (vec![1, 2, 3], ['a', 'b'], ["x", "y", "z"]).try_into_datapoints();
+        match result {
+            Err(Error::ColumnLengths { lengths }) => assert_eq!(lengths, [3, 2, 3]),
+            _ => panic!("Expected columns to differ in length"),
+        }
+    }
+}
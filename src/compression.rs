@@ -0,0 +1,289 @@
+/*!
+Compressed csv-files using the [`Compression`] enum, with each codec enabled by a cargo feature
+*/
+
+use std::ffi::OsStr;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/**
+Compression of csv-files, using the codecs enabled by the `gzip`, `zstd` and `xz` features.
+
+When saving or loading a file, the compression is chosen from the extension of the file
+(such as `.csv.gz`, `.csv.zst` or `.csv.xz`), unless it is given explicitly using
+[`SaveOptions::with_compression`](crate::SaveOptions::with_compression).
+Files with other extensions are plain csv,
+while files with the extension of a codec which is not enabled can not be saved or loaded
+(unless the compression is given explicitly).
+
+Appending to a compressed file adds another compressed stream to the end of the file,
+which is read back as one when loading.
+A [`DatasetWriter`](crate::DatasetWriter) can stream compressed data by wrapping the file in an encoder,
+and using [`from_writer`](crate::DatasetWriter::from_writer).
+
+```
+use delfi::{Compression, Dataset};
+# #[cfg(feature = "gzip")]
+# {
+
+let path = std::env::temp_dir().join("delfi-compressed.csv.gz");
+let ds = Dataset::from_datapoints([[1, 2], [3, 4]]).with_labels(["a", "b"]);
+ds.save(&path).unwrap();
+
+assert_eq!(Compression::from_path(&path), Compression::Gzip);
+let loaded: Dataset<2, [u8; 2]> = Dataset::load(&path).unwrap();
+assert_eq!(loaded.n_datapoints(), 2);
+# std::fs::remove_file(&path).unwrap();
+# }
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Plain csv, without compression
+    #[default]
+    None,
+    /// Gzip compression, with the extension `.gz`
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard compression, with the extension `.zst`
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// Xz compression, with the extension `.xz`
+    #[cfg(feature = "xz")]
+    Xz,
+}
+
+/// All enabled codecs
+const CODECS: &[Compression] = &[
+    #[cfg(feature = "gzip")]
+    Compression::Gzip,
+    #[cfg(feature = "zstd")]
+    Compression::Zstd,
+    #[cfg(feature = "xz")]
+    Compression::Xz,
+];
+
+/// The extensions of all codecs, including those which are not enabled, with their features
+const EXTENSIONS: &[(&str, &str)] = &[("gz", "gzip"), ("zst", "zstd"), ("xz", "xz")];
+
+impl Compression {
+    /**
+    The compression used for a file with the given path, based on its extension.
+    The extension of a codec which is not enabled gives [`Compression::None`].
+    */
+    #[must_use]
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path.extension().and_then(OsStr::to_str);
+        CODECS
+            .iter()
+            .copied()
+            .find(|codec| extension.is_some() && codec.extension() == extension)
+            .unwrap_or_default()
+    }
+
+    /**
+    The file extension of the codec (without the leading `.`), if any
+    */
+    #[must_use]
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            #[cfg(feature = "gzip")]
+            Self::Gzip => Some("gz"),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => Some("zst"),
+            #[cfg(feature = "xz")]
+            Self::Xz => Some("xz"),
+        }
+    }
+
+    /**
+    The compression of a file with the given path, based on its extension,
+    or an error if the extension belongs to a codec which is not enabled
+    */
+    pub(crate) fn for_path(path: &Path) -> io::Result<Self> {
+        let compression = Self::from_path(path);
+        let extension = path.extension().and_then(OsStr::to_str);
+        let disabled = EXTENSIONS
+            .iter()
+            .find(|(codec, _)| compression == Self::None && Some(*codec) == extension);
+        if let Some((extension, feature)) = disabled {
+            let message = format!(
+                "compression feature not enabled, \
+                 files with the extension `.{extension}` require the `{feature}` feature"
+            );
+            return Err(io::Error::new(io::ErrorKind::Unsupported, message));
+        }
+        Ok(compression)
+    }
+}
+
+/**
+A writer which compresses everything written to it using one of the codecs.
+The encoder must be finished to write the end of the compressed stream.
+*/
+pub(crate) enum Encoder<W: Write> {
+    Plain(W),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+    #[cfg(feature = "xz")]
+    Xz(xz2::write::XzEncoder<W>),
+}
+
+impl<W: Write> Encoder<W> {
+    /**
+    Create an encoder using the given compression level, clamped to the levels of the codec.
+    Without a level the default level of the codec is used.
+    */
+    // Creating and finishing an encoder can fail for some codecs, depending on the enabled features
+    #[allow(clippy::unnecessary_wraps)]
+    #[cfg_attr(
+        not(any(feature = "gzip", feature = "zstd", feature = "xz")),
+        allow(unused_variables)
+    )]
+    pub(crate) fn new(writer: W, compression: Compression, level: Option<u32>) -> io::Result<Self> {
+        Ok(match compression {
+            Compression::None => Self::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                let level = level.map_or_else(flate2::Compression::default, |level| {
+                    flate2::Compression::new(level.min(9))
+                });
+                Self::Gzip(flate2::write::GzEncoder::new(writer, level))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                // Level 0 is the default level of zstd
+                let level = level.map_or(0, |level| level.clamp(1, 22));
+                let level = i32::try_from(level).expect("zstd levels fit in an i32");
+                Self::Zstd(zstd::stream::write::Encoder::new(writer, level)?)
+            }
+            #[cfg(feature = "xz")]
+            Compression::Xz => Self::Xz(xz2::write::XzEncoder::new(
+                writer,
+                level.map_or(6, |level| level.min(9)),
+            )),
+        })
+    }
+
+    /// Write the end of the compressed stream, and return the underlying writer
+    #[allow(clippy::unnecessary_wraps)]
+    pub(crate) fn finish(self) -> io::Result<W> {
+        match self {
+            Self::Plain(writer) => Ok(writer),
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder.finish(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.finish(),
+            #[cfg(feature = "xz")]
+            Self::Xz(encoder) => encoder.finish(),
+        }
+    }
+
+    /// The writer being compressed to
+    fn get_mut(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(writer) => writer,
+            #[cfg(feature = "gzip")]
+            Self::Gzip(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder,
+            #[cfg(feature = "xz")]
+            Self::Xz(encoder) => encoder,
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.get_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.get_mut().flush()
+    }
+}
+
+/**
+Create a reader which decompresses everything read from the given reader,
+including several compressed streams following one another
+*/
+#[allow(clippy::unnecessary_wraps)]
+pub(crate) fn decoder<'a, R: Read + 'a>(
+    reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_path() {
+        assert_eq!(
+            Compression::from_path(Path::new("data.csv")),
+            Compression::None
+        );
+        assert_eq!(Compression::from_path(Path::new("data")), Compression::None);
+        for &codec in CODECS {
+            let extension = codec.extension().unwrap();
+            let path = format!("data.csv.{extension}");
+            assert_eq!(Compression::from_path(Path::new(&path)), codec);
+        }
+    }
+
+    #[test]
+    fn for_path() {
+        assert_eq!(
+            Compression::for_path(Path::new("data.csv")).unwrap(),
+            Compression::None
+        );
+        for &(extension, _) in EXTENSIONS {
+            let path = format!("data.csv.{extension}");
+            let result = Compression::for_path(Path::new(&path));
+            if let Some(&codec) = CODECS
+                .iter()
+                .find(|codec| codec.extension() == Some(extension))
+            {
+                assert_eq!(result.unwrap(), codec);
+            } else {
+                let error = result.unwrap_err();
+                assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+                assert!(error.to_string().contains("not enabled"), "{error}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "a,b\n1,2\n".repeat(100);
+        for &codec in [Compression::None].iter().chain(CODECS) {
+            // Two streams following one another are read as one
+            let mut compressed = Vec::new();
+            for level in [None, Some(1)] {
+                let mut encoder = Encoder::new(compressed, codec, level).unwrap();
+                encoder.write_all(text.as_bytes()).unwrap();
+                compressed = encoder.finish().unwrap();
+            }
+            let mut decompressed = String::new();
+            decoder(compressed.as_slice(), codec)
+                .unwrap()
+                .read_to_string(&mut decompressed)
+                .unwrap();
+            assert_eq!(decompressed, text.repeat(2));
+        }
+    }
+}
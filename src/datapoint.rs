@@ -2,37 +2,63 @@
 Implementations on the [Datapoint] trait
 */
 
+use crate::field::{self, Field, RecordBuf};
 use crate::Datapoint;
 
-impl<const N: usize, Data: ToString> Datapoint<N> for [Data; N] {
+/**
+Arrays (and tuples) of elements implementing [`Field`].
+Elements which only implement [`Display`](std::fmt::Display) can be wrapped in [`Displayed`](crate::field::Displayed).
+*/
+impl<const N: usize, Data: Field> Datapoint<N> for [Data; N] {
     fn record(&self) -> [String; N] {
-        self.iter()
-            .map(Data::to_string)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+        field::record(self)
     }
-}
 
-impl<D1: ToString> Datapoint<1> for (D1,) {
-    fn record(&self) -> [String; 1] {
-        [self.0.to_string()]
+    fn write_record(&self, record: &mut RecordBuf) {
+        for element in self {
+            record.push(element);
+        }
     }
 }
 
-impl<D1: ToString, D2: ToString> Datapoint<2> for (D1, D2) {
-    fn record(&self) -> [String; 2] {
-        [self.0.to_string(), self.1.to_string()]
-    }
-}
+/// Implement Datapoint for a tuple, given its arity and the type parameter and index of each element
+macro_rules! impl_datapoint_for_tuple {
+    ($n:literal => $($name:ident $index:tt),+) => {
+        impl<$($name: Field),+> Datapoint<$n> for ($($name,)+) {
+            fn record(&self) -> [String; $n] {
+                field::record(self)
+            }
 
-impl<D1: ToString, D2: ToString, D3: ToString> Datapoint<3> for (D1, D2, D3) {
-    fn record(&self) -> [String; 3] {
-        [self.0.to_string(), self.1.to_string(), self.2.to_string()]
-    }
+            fn write_record(&self, record: &mut RecordBuf) {
+                $(record.push(&self.$index);)+
+            }
+        }
+    };
 }
 
+impl_datapoint_for_tuple!(1 => D1 0);
+impl_datapoint_for_tuple!(2 => D1 0, D2 1);
+impl_datapoint_for_tuple!(3 => D1 0, D2 1, D3 2);
+impl_datapoint_for_tuple!(4 => D1 0, D2 1, D3 2, D4 3);
+impl_datapoint_for_tuple!(5 => D1 0, D2 1, D3 2, D4 3, D5 4);
+impl_datapoint_for_tuple!(6 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5);
+impl_datapoint_for_tuple!(7 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6);
+impl_datapoint_for_tuple!(8 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7);
+impl_datapoint_for_tuple!(9 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8);
+impl_datapoint_for_tuple!(10 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9);
+impl_datapoint_for_tuple!(11 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10);
+impl_datapoint_for_tuple!(12 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10, D12 11);
+impl_datapoint_for_tuple!(13 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10, D12 11, D13 12);
+impl_datapoint_for_tuple!(14 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10, D12 11, D13 12, D14 13);
+impl_datapoint_for_tuple!(15 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10, D12 11, D13 12, D14 13, D15 14);
+impl_datapoint_for_tuple!(16 => D1 0, D2 1, D3 2, D4 3, D5 4, D6 5, D7 6, D8 7, D9 8, D10 9, D11 10, D12 11, D13 12, D14 13, D15 14, D16 15);
+
 #[cfg(test)]
+#[allow(
+    clippy::approx_constant,
+    clippy::needless_pass_by_value,
+    clippy::semicolon_if_nothing_returned
+)]
 mod tests {
     use super::*;
 
@@ -75,6 +101,24 @@ mod tests {
         recording(vec![&datapoint_1, &datapoint_2]);
     }
 
+    #[test]
+    fn long_tuples() {
+        let data = (
+            0.5, 1, 'x', "y", -2, 3.25, true, 8_u8, 9_i64, 10_usize, 11.5, "twelve",
+        );
+        let datapoint: &dyn Datapoint<12> = &data;
+        let record = datapoint.record();
+        let compare = [
+            "0.5", "1", "x", "y", "-2", "3.25", "true", "8", "9", "10", "11.5", "twelve",
+        ]
+        .map(String::from);
+        assert_eq!(record, compare);
+
+        let data = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, "sixteen");
+        let datapoint: &dyn Datapoint<16> = &data;
+        assert_eq!(datapoint.record()[15], "sixteen");
+    }
+
     #[test]
     fn tuples() {
         let data = ("hello", 4);
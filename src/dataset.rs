@@ -2,10 +2,24 @@
 Implementations on the [Dataset] struct
 */
 
+use std::fs::File;
+use std::io;
 use std::path::Path;
 
+use crate::columns::Columns;
+use crate::compression;
+use crate::field::Field;
+use crate::format::FloatFormats;
+use crate::json::{JsonLayout, JsonWriter};
+use crate::output;
 use crate::Datapoint;
 use crate::Dataset;
+use crate::Error;
+use crate::FloatFormat;
+use crate::FromRecord;
+use crate::Header;
+use crate::Labelled;
+use crate::SaveOptions;
 
 impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     /**
@@ -15,6 +29,7 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     pub fn new() -> Self {
         Self {
             labels: None,
+            float_formats: FloatFormats::default(),
             data: Vec::new(),
         }
     }
@@ -53,6 +68,13 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
         COLS
     }
 
+    /**
+    Split dataset into its labels, datapoints and float formats
+    */
+    pub(crate) fn into_parts(self) -> (Option<[String; COLS]>, Vec<Data>, FloatFormats) {
+        (self.labels, self.data, self.float_formats)
+    }
+
     /**
     Get current labels
     */
@@ -103,14 +125,8 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     where
         Labels: Into<Option<[&'a str; COLS]>>,
     {
-        let labels: Option<[String; COLS]> = labels.into().map(|labels| {
-            labels
-                .into_iter()
-                .map(ToOwned::to_owned)
-                .collect::<Vec<String>>()
-                .try_into()
-                .expect("Failed to coerce vec into array")
-        });
+        let labels: Option<[String; COLS]> =
+            labels.into().map(|labels| labels.map(ToOwned::to_owned));
         self.labels = labels;
     }
 
@@ -125,7 +141,7 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     let _ = Dataset::from_columns([&t, &x]).with_labels(["time", "length"]);
     ```
 
-    See set_labels() for detail on possible parameters.
+    See `set_labels()` for detail on possible parameters.
     */
     #[must_use]
     pub fn with_labels<'a, Labels>(mut self, labels: Labels) -> Self
@@ -136,6 +152,54 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
         self
     }
 
+    /**
+    Get the float format used for the given column
+    */
+    #[must_use]
+    pub fn get_float_format(&self, column: usize) -> FloatFormat {
+        self.float_formats.get(column)
+    }
+
+    /**
+    Set the float format used for all columns without a format of their own, see [`FloatFormat`]
+    */
+    pub fn set_float_format(&mut self, format: FloatFormat) {
+        self.float_formats.set_default(format);
+    }
+
+    /**
+    Take dataset, set the float format used for all columns without a format of their own, and return dataset
+    */
+    #[must_use]
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.set_float_format(format);
+        self
+    }
+
+    /**
+    Set the float format used for the given column, overriding the format of the dataset
+    */
+    pub fn set_column_float_format(&mut self, column: usize, format: FloatFormat) {
+        self.float_formats.set_column(column, format);
+    }
+
+    /**
+    Take dataset, set the float format used for the given column, and return dataset
+
+    ```
+    use delfi::{Dataset, FloatFormat};
+
+    let dataset = Dataset::from_datapoints([[1.0 / 3.0, 1.0 / 3.0]])
+        .with_column_float_format(1, FloatFormat::Scientific(2));
+    assert_eq!(dataset.to_csv_string().unwrap(), "0.3333333333333333,3.33e-1\n");
+    ```
+    */
+    #[must_use]
+    pub fn with_column_float_format(mut self, column: usize, format: FloatFormat) -> Self {
+        self.set_column_float_format(column, format);
+        self
+    }
+
     /**
     Create a dataset from an iterator over datapoints
     */
@@ -148,9 +212,47 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     {
         Self {
             labels: None,
+            float_formats: FloatFormats::default(),
             data: rows.into_iter().collect(),
         }
     }
+
+    /**
+    Takes in a tuple of columns, each of which may have a different type of element,
+    and creates a dataset of tuples from these. Stops at the end of the shortest column.
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let steps: Vec<usize> = vec![0, 1, 2];
+    let energies: Vec<f64> = vec![1.5, 1.25, 1.125];
+    let phases = ["solid", "liquid", "gas"];
+    let dataset = Dataset::from_mixed_columns((steps, energies, phases));
+    assert_eq!(dataset.n_columns(), 3);
+    ```
+    */
+    #[must_use]
+    pub fn from_mixed_columns<C>(columns: C) -> Self
+    where
+        C: Columns<COLS, Datapoint = Data>,
+    {
+        Self::from_datapoints(columns.into_datapoints())
+    }
+
+    /**
+    Takes in a tuple of columns, each of which may have a different type of element,
+    and creates a dataset of tuples from these, requiring all columns to be of equal length.
+
+    # Errors
+    Returns an error containing the length of each column if the columns differ in length
+    */
+    pub fn try_from_mixed_columns<C>(columns: C) -> Result<Self, Error>
+    where
+        C: Columns<COLS, Datapoint = Data>,
+    {
+        columns.try_into_datapoints().map(Self::from_datapoints)
+    }
 }
 
 /**
@@ -162,9 +264,10 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Default for Dataset<COLS, Data> {
     }
 }
 
-impl<const COLS: usize, DataElement: ToString> Dataset<COLS, [DataElement; COLS]> {
+impl<const COLS: usize, DataElement: Field> Dataset<COLS, [DataElement; COLS]> {
     /**
     Takes in a set of columns and creates a dataset from these.
+    Elements which only implement [`Display`](std::fmt::Display) can be wrapped in [`Displayed`](crate::field::Displayed).
 
     # Examples
     ```
@@ -180,42 +283,132 @@ impl<const COLS: usize, DataElement: ToString> Dataset<COLS, [DataElement; COLS]
         IntoIter: IntoIterator<Item = DataElement, IntoIter = Iter>,
         Iter: Iterator<Item = DataElement>,
     {
-        let mut columns: [Iter; COLS] = columns
-            .into_iter()
-            .map(IntoIterator::into_iter)
-            .collect::<Vec<Iter>>()
-            .try_into()
-            .map_err(|_| ())
-            .expect("Failed to coerce vec into array");
+        let mut columns: [Iter; COLS] = columns.map(IntoIterator::into_iter);
         let mut data = Vec::new();
-        'outer: loop {
-            let mut temp = Vec::with_capacity(COLS);
-            for col in columns.iter_mut() {
-                if let Some(data) = col.next() {
-                    temp.push(data);
-                } else {
-                    break 'outer;
-                }
+        // Stops as soon as any of the columns run dry
+        while let Some(row) = complete(columns.each_mut().map(Iterator::next)) {
+            data.push(row);
+        }
+
+        Self::from_datapoints(data)
+    }
+
+    /**
+    Takes in a set of columns and creates a dataset from these, requiring all columns to be of equal length.
+    Unlike [`Dataset::from_columns`] the columns are not silently truncated to the shortest column.
+
+    # Examples
+    ```
+    use delfi::{Dataset, Error};
+
+    let t = [0, 1, 2, 3, 4, 5];
+    let x = [2, 3, 5, 8, 12];
+    let result = Dataset::try_from_columns([&t[..], &x[..]]);
+    assert!(matches!(result, Err(Error::ColumnLengths { lengths }) if lengths == [6, 5]));
+    ```
+
+    # Errors
+    Returns an error containing the length of each column if the columns differ in length
+    */
+    pub fn try_from_columns<IntoIter, Iter>(columns: [IntoIter; COLS]) -> Result<Self, Error>
+    where
+        IntoIter: IntoIterator<Item = DataElement, IntoIter = Iter>,
+        Iter: Iterator<Item = DataElement>,
+    {
+        let mut columns: [Iter; COLS] = columns.map(IntoIterator::into_iter);
+        let mut data = Vec::new();
+        loop {
+            let row: [Option<DataElement>; COLS] = columns.each_mut().map(Iterator::next);
+            let present: [bool; COLS] = row.each_ref().map(Option::is_some);
+            if !present.contains(&true) {
+                break;
             }
-            // map_err is required to avoid restricting Debug to be implemented for IntoIterator
-            let row: [DataElement; COLS] = temp
-                .try_into()
-                .map_err(|_| ())
-                .expect("Failed to coerce vec into array");
+            let Some(row) = complete(row) else {
+                // Count what remains of each column to report the full lengths
+                let lengths = columns
+                    .into_iter()
+                    .zip(present)
+                    .map(|(rest, present)| data.len() + usize::from(present) + rest.count())
+                    .collect();
+                return Err(Error::ColumnLengths { lengths });
+            };
             data.push(row);
         }
 
-        let labels = None;
+        Ok(Self::from_datapoints(data))
+    }
+
+    /**
+    Takes in a set of columns and creates a dataset from these,
+    filling in the missing elements of shorter columns with the given placeholder.
+
+    # Examples
+    ```
+    use delfi::Dataset;
 
-        Dataset { labels, data }
+    let t = vec![0.0, 0.5, 1.0];
+    let x = vec![2.0, 3.5];
+    let dataset = Dataset::from_columns_padded([t, x], &f64::NAN);
+    assert_eq!(dataset.n_datapoints(), 3);
+    ```
+    */
+    pub fn from_columns_padded<IntoIter, Iter>(
+        columns: [IntoIter; COLS],
+        placeholder: &DataElement,
+    ) -> Self
+    where
+        IntoIter: IntoIterator<Item = DataElement, IntoIter = Iter>,
+        Iter: Iterator<Item = DataElement>,
+        DataElement: Clone,
+    {
+        let mut columns: [Iter; COLS] = columns.map(IntoIterator::into_iter);
+        let mut data = Vec::new();
+        loop {
+            let row: [Option<DataElement>; COLS] = columns.each_mut().map(Iterator::next);
+            if row.iter().all(Option::is_none) {
+                break;
+            }
+            data.push(row.map(|element| element.unwrap_or_else(|| placeholder.clone())));
+        }
+
+        Self::from_datapoints(data)
     }
 }
 
+impl<const COLS: usize, Data: Labelled<COLS>> Dataset<COLS, Data> {
+    /**
+    Create a dataset from an iterator over datapoints, labelled using the labels of the datapoints
+    */
+    #[must_use]
+    pub fn from_labelled_datapoints<IntoIter, Iter>(rows: IntoIter) -> Self
+    where
+        IntoIter: IntoIterator<Item = Data, IntoIter = Iter>,
+        Iter: Iterator<Item = Data>,
+    {
+        Self::from_datapoints(rows).with_default_labels()
+    }
+
+    /**
+    Take dataset, set labels to the labels of the datapoints, and return dataset
+    */
+    #[must_use]
+    pub fn with_default_labels(self) -> Self {
+        self.with_labels(Data::LABELS)
+    }
+}
+
+/// Turn a row of elements into a complete row, if none of the elements are missing
+fn complete<T, const COLS: usize>(row: [Option<T>; COLS]) -> Option<[T; COLS]> {
+    row.into_iter().collect::<Option<Vec<T>>>()?.try_into().ok()
+}
+
 impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     /**
     Saves a dataset to a given file. The filepath must be valid.
     Accepts anything path-like.
 
+    The dataset is borrowed, so it can be saved as a snapshot and then be added to further.
+
     # Examples
     ```
     # use delfi::Dataset;
@@ -239,21 +432,429 @@ impl<const COLS: usize, Data: Datapoint<COLS>> Dataset<COLS, Data> {
     dataset.save(&filepath).unwrap();
     ```
 
+    # Errors
+    Returns an error if the file could not be created or written to
+    */
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Error> {
+        self.save_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Saves a dataset to a given file, using the given options for the csv dialect.
+    The filepath must be valid. Accepts anything path-like.
+
+    # Examples
+    ```
+    use delfi::{Dataset, SaveOptions};
+
+    let t = [0, 1, 2, 3, 4, 5];
+    let x = [2, 3, 5, 8, 12, 17];
+    let dataset = Dataset::from_columns([t, x]).with_labels(["time", "length"]);
+
+    let options = SaveOptions::new().with_delimiter(b'\t').with_header(false);
+    dataset.save_with("./resources/data/examples/save-with.tsv", &options).unwrap();
+    ```
+
+    # Errors
+    Returns an error if the file could not be created or written to
+    */
+    pub fn save_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        output::save(filepath.as_ref(), options, |file| {
+            self.write_to_with(file, options)
+        })
+    }
+
+    /**
+    Appends a dataset to the end of a given file, which is created if it does not exist.
+    Accepts anything path-like.
+
+    If the file already has rows, its header row must match the labels of the dataset,
+    and the header row is not written again. Without labels only the number of columns is checked.
+
+    # Examples
+    ```
+    use delfi::Dataset;
+    # let _ = std::fs::remove_file("./resources/data/examples/append.csv");
+
+    let day1 = Dataset::from_datapoints([[1, 12], [2, 15]]).with_labels(["run", "score"]);
+    let day2 = Dataset::from_datapoints([[3, 14]]).with_labels(["run", "score"]);
+    day1.append("./resources/data/examples/append.csv").unwrap();
+    day2.append("./resources/data/examples/append.csv").unwrap();
+
+    let all: Dataset<2, [u32; 2]> = Dataset::load("./resources/data/examples/append.csv").unwrap();
+    assert_eq!(all.n_datapoints(), 3);
+    ```
+
+    # Errors
+    Returns an error if the file could not be opened, read or written to,
+    or if its header row does not match the labels (or number of columns) of the dataset
+    */
+    pub fn append<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Error> {
+        self.append_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Appends a dataset to the end of a given file, using the given options for the csv dialect.
+    See [`append`](Dataset::append) for how the existing header row is checked.
+
+    # Errors
+    Returns an error if the file could not be opened, read or written to,
+    or if its header row does not match the labels (or number of columns) of the dataset
+    */
+    pub fn append_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        let filepath = filepath.as_ref();
+        let output::Appending { file, first, temp } = output::open_append(filepath, options)?;
+        let result = match first {
+            None => output::encode(file, filepath, options, |encoder| {
+                self.write_to_with(encoder, options)
+            }),
+            Some(first) => {
+                let labels = self.labels.as_ref().filter(|_| options.header());
+                let options = options.clone().with_header(false);
+                output::check_existing(&first, labels.map(|labels| &labels[..]), COLS).and_then(
+                    |()| {
+                        output::encode(file, filepath, &options, |encoder| {
+                            self.write_to_with(encoder, &options)
+                        })
+                    },
+                )
+            }
+        };
+        result
+            .and_then(|()| temp.map_or(Ok(()), output::TempFile::commit))
+            .map_err(|e| e.at_path(filepath))
+    }
+
+    /**
+    Writes a dataset as csv to anything implementing [`std::io::Write`],
+    such as stdout, a socket or an in-memory buffer.
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let t = [0, 1, 2];
+    let x = [2, 3, 5];
+    let dataset = Dataset::from_columns([t, x]).with_labels(["time", "length"]);
+
+    let mut buffer = Vec::new();
+    dataset.write_to(&mut buffer).unwrap();
+    assert_eq!(buffer, b"time,length\n0,2\n1,3\n2,5\n");
+    ```
+
+    # Errors
+    Returns an error if writing to the writer failed
     */
-    pub fn save<P: AsRef<Path>>(self, filepath: P) -> Result<(), std::io::Error> {
-        let mut writer = csv::Writer::from_path(filepath)?;
-        if let Some(labels) = self.labels {
-            writer.write_record(&labels)?;
+    pub fn write_to<W: io::Write>(&self, writer: W) -> Result<(), Error> {
+        self.write_to_with(writer, &SaveOptions::default())
+    }
+
+    /**
+    Writes a dataset as csv to anything implementing [`std::io::Write`],
+    using the given options for the csv dialect
+
+    # Errors
+    Returns an error if writing to the writer failed
+    */
+    pub fn write_to_with<W: io::Write>(
+        &self,
+        writer: W,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        let mut writer = output::CsvWriter::new(writer, options);
+        writer.float_formats_mut().clone_from(&self.float_formats);
+        if let Some(labels) = &self.labels {
+            writer.write_labels(labels)?;
+        }
+        for datapoint in &self.data {
+            writer.write_datapoint(datapoint)?;
         }
-        for datapoint in self.data {
-            writer.write_record(datapoint.record())?;
+        writer.flush()
+    }
+
+    /**
+    Writes a dataset as csv to a string
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let dataset = Dataset::from_datapoints([("a", 1), ("b", 2)]).with_labels(["name", "count"]);
+    assert_eq!(dataset.to_csv_string().unwrap(), "name,count\na,1\nb,2\n");
+    ```
+
+    # Errors
+    Returns an error if any of the datapoints could not be written
+    */
+    pub fn to_csv_string(&self) -> Result<String, Error> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+        String::from_utf8(buffer)
+            .map_err(|e| Error::from(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+
+    /**
+    Saves a dataset as JSON to a given file, using the given layout (see [`JsonLayout`]).
+    The filepath must be valid. Accepts anything path-like.
+
+    # Examples
+    ```
+    use delfi::{Dataset, JsonLayout};
+
+    let t = [0.0, 0.5, 1.0];
+    let x = [1.0, 2.0, 4.0];
+    let dataset = Dataset::from_columns([t, x]).with_labels(["time", "length"]);
+    dataset.save_json("./resources/data/examples/records.json", JsonLayout::Records).unwrap();
+    dataset.save_json("./resources/data/examples/columns.json", JsonLayout::Columns).unwrap();
+    ```
+
+    # Errors
+    Returns an error if the file could not be created or written to,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn save_json<P: AsRef<Path>>(&self, filepath: P, layout: JsonLayout) -> Result<(), Error> {
+        self.save_json_with(filepath, layout, &SaveOptions::default())
+    }
+
+    /**
+    Saves a dataset as JSON to a given file, using the given layout.
+    Of the options, only those for atomic saving and compression are used.
+
+    # Errors
+    Returns an error if the file could not be created or written to,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn save_json_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        layout: JsonLayout,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        output::save(filepath.as_ref(), options, |encoder| {
+            self.write_json_to(encoder, layout)
+        })
+    }
+
+    /**
+    Writes a dataset as JSON to anything implementing [`std::io::Write`], using the given layout
+
+    # Errors
+    Returns an error if writing to the writer failed,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn write_json_to<W: io::Write>(&self, writer: W, layout: JsonLayout) -> Result<(), Error> {
+        let labels = self.labels.as_ref().map(|labels| &labels[..]);
+        JsonWriter::new(writer, labels, &self.float_formats).write(&self.data, layout)
+    }
+
+    /**
+    Saves a dataset as JSON Lines to a given file, with one object (or array, without labels) per line.
+    The filepath must be valid. Accepts anything path-like.
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let dataset = Dataset::from_datapoints([(0, "a"), (1, "b")]).with_labels(["step", "name"]);
+    dataset.save_jsonl("./resources/data/examples/lines.jsonl").unwrap();
+
+    let mut buffer = Vec::new();
+    dataset.write_jsonl_to(&mut buffer).unwrap();
+    assert_eq!(buffer, b"{\"step\":0,\"name\":\"a\"}\n{\"step\":1,\"name\":\"b\"}\n");
+    ```
+
+    # Errors
+    Returns an error if the file could not be created or written to,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn save_jsonl<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Error> {
+        self.save_jsonl_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Saves a dataset as JSON Lines to a given file.
+    Of the options, only those for atomic saving and compression are used.
+
+    # Errors
+    Returns an error if the file could not be created or written to,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn save_jsonl_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        output::save(filepath.as_ref(), options, |encoder| {
+            self.write_jsonl_to(encoder)
+        })
+    }
+
+    /**
+    Writes a dataset as JSON Lines to anything implementing [`std::io::Write`]
+
+    # Errors
+    Returns an error if writing to the writer failed,
+    or if a datapoint does not write exactly `COLS` fields
+    */
+    pub fn write_jsonl_to<W: io::Write>(&self, writer: W) -> Result<(), Error> {
+        let labels = self.labels.as_ref().map(|labels| &labels[..]);
+        JsonWriter::new(writer, labels, &self.float_formats).write_lines(&self.data)
+    }
+}
+
+impl<const COLS: usize, Data: Datapoint<COLS> + FromRecord<COLS>> Dataset<COLS, Data> {
+    /**
+    Loads a dataset from a given file. Accepts anything path-like.
+    The first row is used as labels if it can not be parsed as a datapoint, see [`Header`].
+    Compressed files are decompressed based on their extension, see [`Compression`](crate::Compression).
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let dataset: Dataset<2, [f64; 2]> = Dataset::load("./resources/data/examples/load.csv").unwrap();
+    assert_eq!(dataset.get_labels().unwrap(), &["time", "length"]);
+    assert_eq!(dataset.n_datapoints(), 3);
+    ```
+
+    # Errors
+    Returns an error if the file could not be read (or is compressed by a codec which is not enabled),
+    if any row does not have exactly `COLS` fields,
+    or if any row (except the header row) could not be parsed
+    */
+    pub fn load<P: AsRef<Path>>(filepath: P) -> Result<Self, Error> {
+        Self::load_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Loads a dataset from a given file, in the csv dialect of the given options.
+    Whether the first row is used as labels is set by the header of the options, see [`Header`].
+
+    # Examples
+    ```
+    use delfi::{Dataset, SaveOptions};
+
+    let options = SaveOptions::new().with_delimiter(b';').with_header(false);
+    let ds = Dataset::from_datapoints([[1, 2], [3, 4]]);
+    ds.save_with("./resources/data/examples/load-options.csv", &options).unwrap();
+
+    let loaded: Dataset<2, [u8; 2]> = Dataset::load_with("./resources/data/examples/load-options.csv", &options).unwrap();
+    assert_eq!(loaded.get_labels(), None);
+    assert_eq!(loaded.n_datapoints(), 2);
+    ```
+
+    # Errors
+    Returns an error if the file could not be read (or is compressed by a codec which is not enabled),
+    if any row does not have exactly `COLS` fields,
+    or if any row (except a header row) could not be parsed
+    */
+    pub fn load_with<P: AsRef<Path>>(filepath: P, options: &SaveOptions) -> Result<Self, Error> {
+        let filepath = filepath.as_ref();
+        options
+            .compression_for(filepath)
+            .and_then(|compression| compression::decoder(File::open(filepath)?, compression))
+            .map_err(Error::from)
+            .and_then(|reader| Self::read_from_with(reader, options))
+            .map_err(|e| e.at_path(filepath))
+    }
+
+    /**
+    Reads a dataset as csv from anything implementing [`std::io::Read`],
+    with the first row as labels if it can not be parsed as a datapoint.
+
+    # Examples
+    ```
+    use delfi::Dataset;
+
+    let csv = "x,y\n1,2\n3,4\n";
+    let dataset: Dataset<2, [u8; 2]> = Dataset::read_from(csv.as_bytes()).unwrap();
+    assert_eq!(dataset.n_rows(), 3);
+    ```
+
+    # Errors
+    Returns an error if reading failed,
+    if any row does not have exactly `COLS` fields,
+    or if any row (except the header row) could not be parsed
+    */
+    pub fn read_from<R: io::Read>(reader: R) -> Result<Self, Error> {
+        Self::read_from_with(reader, &SaveOptions::default())
+    }
+
+    /**
+    Reads a dataset as csv from anything implementing [`std::io::Read`], in the csv dialect of the given options.
+    See [`Dataset::load_with`].
+
+    # Examples
+    ```
+    use delfi::{Dataset, SaveOptions};
+
+    let csv = "1,2\n3,4\n";
+    let options = SaveOptions::new().with_header(false);
+    let dataset: Dataset<2, [u8; 2]> = Dataset::read_from_with(csv.as_bytes(), &options).unwrap();
+    assert_eq!(dataset.n_datapoints(), 2);
+    ```
+
+    # Errors
+    Returns an error if reading failed,
+    if any row does not have exactly `COLS` fields,
+    or if any row (except a header row) could not be parsed
+    */
+    pub fn read_from_with<R: io::Read>(reader: R, options: &SaveOptions) -> Result<Self, Error> {
+        let mut reader = options.reader(reader);
+        let mut dataset = Self::new();
+        for (row, result) in reader.records().enumerate() {
+            let fields: Vec<String> = result?.iter().map(ToOwned::to_owned).collect();
+            let record: [String; COLS] =
+                fields
+                    .try_into()
+                    .map_err(|fields: Vec<String>| Error::Shape {
+                        path: None,
+                        row,
+                        expected: COLS,
+                        found: fields.len(),
+                    })?;
+
+            if row == 0 && options.header_mode() == Header::Present {
+                dataset.labels = Some(record);
+                continue;
+            }
+            // Keep the first row for the labels, in case it is a header row rather than a datapoint
+            let first = (row == 0 && options.header_mode() == Header::Auto).then(|| record.clone());
+            match Data::from_record(record) {
+                Ok(datapoint) => dataset.push(datapoint),
+                Err(_) if first.is_some() => dataset.labels = first,
+                Err(mut source) => {
+                    // Report the label of the column, unless the datapoint knows the field name.
+                    // A datapoint may report a column beyond the labels, which is named by its index
+                    if let (None, Some(labels)) = (source.field(), &dataset.labels) {
+                        let label = match labels.get(source.column()) {
+                            Some(label) => label.clone(),
+                            None => source.column().to_string(),
+                        };
+                        source = source.with_field(&label);
+                    }
+                    return Err(Error::Parse {
+                        path: None,
+                        row,
+                        source,
+                    });
+                }
+            }
         }
-        writer.flush()?;
-        Ok(())
+        Ok(dataset)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_pass_by_value, clippy::uninlined_format_args)]
 mod tests {
     use super::*;
 
@@ -295,6 +896,88 @@ mod tests {
         assert_eq!(dataset.n_rows(), 3);
     }
 
+    #[test]
+    fn write_to() {
+        let mut dataset = Dataset::new().with_labels(["a", "b"]);
+        dataset.push([1, 2]);
+        dataset.push([3, 4]);
+        let mut buffer = Vec::new();
+        dataset.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer, b"a,b\n1,2\n3,4\n");
+    }
+
+    #[test]
+    fn to_csv_string() {
+        let dataset = Dataset::from_datapoints([("x, y", 1.5)]);
+        assert_eq!(dataset.to_csv_string().unwrap(), "\"x, y\",1.5\n");
+    }
+
+    #[test]
+    fn float_formats() {
+        let x = [0.1 + 0.2, 1234.5, f64::NAN];
+        let dataset = Dataset::from_columns([x; 4])
+            .with_float_format(FloatFormat::Fixed(2))
+            .with_column_float_format(1, FloatFormat::Significant(3))
+            .with_column_float_format(2, FloatFormat::Scientific(1))
+            .with_column_float_format(3, FloatFormat::RoundTrip);
+        assert_eq!(dataset.get_float_format(0), FloatFormat::Fixed(2));
+        assert_eq!(
+            dataset.to_csv_string().unwrap(),
+            "0.30,0.300,3.0e-1,0.30000000000000004\n\
+             1234.50,1230,1.2e3,1234.5\n\
+             NaN,NaN,NaN,NaN\n"
+        );
+    }
+
+    #[test]
+    fn write_to_with_options() {
+        let dataset = Dataset::from_columns([[1, 2], [3, 4]]).with_labels(["a", "b"]);
+        let options = SaveOptions::new().with_delimiter(b';').with_header(false);
+        let mut buffer = Vec::new();
+        dataset.write_to_with(&mut buffer, &options).unwrap();
+        assert_eq!(buffer, b"1;3\n2;4\n");
+    }
+
+    #[test]
+    fn columns_strict() {
+        let dataset = Dataset::try_from_columns([vec![1, 2], vec![3, 4]]).unwrap();
+        assert_eq!(dataset.n_datapoints(), 2);
+
+        let result = Dataset::try_from_columns([vec![1, 2, 3], vec![3, 4], vec![5, 6, 7]]);
+        match result {
+            Err(Error::ColumnLengths { lengths }) => assert_eq!(lengths, [3, 2, 3]),
+            _ => panic!("Expected columns to differ in length"),
+        }
+    }
+
+    #[test]
+    fn parse_error_beyond_labels() {
+        // A datapoint reporting a column beyond the labels
+        struct Pair;
+        impl Datapoint<2> for Pair {
+            fn record(&self) -> [String; 2] {
+                [String::new(), String::new()]
+            }
+        }
+        impl FromRecord<2> for Pair {
+            fn from_record(_: [String; 2]) -> Result<Self, crate::from_record::ParseError> {
+                Err(crate::from_record::ParseError::length(3, 4))
+            }
+        }
+
+        let result: Result<Dataset<2, Pair>, _> = Dataset::read_from("a,b\n1,2\n".as_bytes());
+        match result {
+            Err(Error::Parse { source, .. }) => assert_eq!(source.field(), Some("3")),
+            _ => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn columns_padded() {
+        let dataset = Dataset::from_columns_padded([vec!["a"], vec!["b", "c"]], &"-");
+        assert_eq!(dataset.to_csv_string().unwrap(), "a,b\n-,c\n");
+    }
+
     // Check constructors
     fn check_size<const COLS: usize, Data: Datapoint<COLS>>(dataset: Dataset<COLS, Data>) {
         assert_eq!(dataset.n_columns(), 2);
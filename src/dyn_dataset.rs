@@ -0,0 +1,810 @@
+/*!
+Datasets with a number of columns only known at runtime, using the [`DynDataset`] struct
+*/
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+use std::io;
+use std::path::Path;
+
+use crate::field::{self, Field, RecordBuf};
+use crate::format::FloatFormats;
+use crate::output;
+use crate::Datapoint;
+use crate::Dataset;
+use crate::Error;
+use crate::FloatFormat;
+use crate::SaveOptions;
+
+/**
+A datapoint with a number of elements only known at runtime.
+
+This is implemented for vectors, slices, maps and (using the same number of elements as
+their [Datapoint] implementation) arrays and tuples. The `Datapoint` derive macro also
+implements this trait.
+*/
+pub trait DynDatapoint {
+    /**
+    Record the datapoint as a number of fields
+    */
+    fn record_dyn(&self) -> Vec<String>;
+
+    /**
+    Record the datapoint with fields in the order of the given labels.
+    Only datapoints which know the labels of their elements (such as maps) make use of the labels.
+    */
+    fn record_labelled(&self, _labels: &[String]) -> Vec<String> {
+        self.record_dyn()
+    }
+
+    /**
+    Write the fields of the datapoint into a reusable buffer, in the order of the labels if given.
+
+    This is used when saving and writing datasets, and writes missing values and non-finite floats
+    as configured by [`SaveOptions`]. The default implementation uses [`record_labelled`](DynDatapoint::record_labelled)
+    and [`record_dyn`](DynDatapoint::record_dyn).
+    */
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        let fields = match labels {
+            Some(labels) => self.record_labelled(labels),
+            None => self.record_dyn(),
+        };
+        for field in fields {
+            record.push_str(&field);
+        }
+    }
+
+    /**
+    Number of fields in the record of the datapoint
+    */
+    fn width(&self) -> usize {
+        self.record_dyn().len()
+    }
+
+    /**
+    Keys of the elements of the datapoint, for datapoints which are recorded by key (such as maps).
+    When pushed to a dataset with labels, the keys must be the same as the labels.
+    */
+    fn keys(&self) -> Option<Vec<&str>> {
+        None
+    }
+}
+
+impl<T: Field> DynDatapoint for [T] {
+    fn record_dyn(&self) -> Vec<String> {
+        self.iter().map(field::to_string).collect()
+    }
+
+    fn write_dyn(&self, _labels: Option<&[String]>, record: &mut RecordBuf) {
+        for element in self {
+            record.push(element);
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: Field> DynDatapoint for Vec<T> {
+    fn record_dyn(&self) -> Vec<String> {
+        self.as_slice().record_dyn()
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        self.as_slice().write_dyn(labels, record);
+    }
+
+    fn width(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: DynDatapoint + ?Sized> DynDatapoint for &T {
+    fn record_dyn(&self) -> Vec<String> {
+        (**self).record_dyn()
+    }
+
+    fn record_labelled(&self, labels: &[String]) -> Vec<String> {
+        (**self).record_labelled(labels)
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        (**self).write_dyn(labels, record);
+    }
+
+    fn width(&self) -> usize {
+        (**self).width()
+    }
+
+    fn keys(&self) -> Option<Vec<&str>> {
+        (**self).keys()
+    }
+}
+
+impl<T: DynDatapoint + ?Sized> DynDatapoint for Box<T> {
+    fn record_dyn(&self) -> Vec<String> {
+        (**self).record_dyn()
+    }
+
+    fn record_labelled(&self, labels: &[String]) -> Vec<String> {
+        (**self).record_labelled(labels)
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        (**self).write_dyn(labels, record);
+    }
+
+    fn width(&self) -> usize {
+        (**self).width()
+    }
+
+    fn keys(&self) -> Option<Vec<&str>> {
+        (**self).keys()
+    }
+}
+
+impl<const N: usize, T: Field> DynDatapoint for [T; N] {
+    fn record_dyn(&self) -> Vec<String> {
+        self.as_slice().record_dyn()
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        self.as_slice().write_dyn(labels, record);
+    }
+
+    fn width(&self) -> usize {
+        N
+    }
+}
+
+/// Implement `DynDatapoint` for a tuple with the given arity, using its `Datapoint` implementation
+macro_rules! impl_dyn_datapoint_for_tuple {
+    ($n:literal => $($name:ident),+) => {
+        impl<$($name: Field),+> DynDatapoint for ($($name,)+) {
+            fn record_dyn(&self) -> Vec<String> {
+                Datapoint::<$n>::record(self).into()
+            }
+
+            fn write_dyn(&self, _labels: Option<&[String]>, record: &mut RecordBuf) {
+                Datapoint::<$n>::write_record(self, record);
+            }
+
+            fn width(&self) -> usize {
+                $n
+            }
+        }
+    };
+}
+
+impl_dyn_datapoint_for_tuple!(1 => D1);
+impl_dyn_datapoint_for_tuple!(2 => D1, D2);
+impl_dyn_datapoint_for_tuple!(3 => D1, D2, D3);
+impl_dyn_datapoint_for_tuple!(4 => D1, D2, D3, D4);
+impl_dyn_datapoint_for_tuple!(5 => D1, D2, D3, D4, D5);
+impl_dyn_datapoint_for_tuple!(6 => D1, D2, D3, D4, D5, D6);
+impl_dyn_datapoint_for_tuple!(7 => D1, D2, D3, D4, D5, D6, D7);
+impl_dyn_datapoint_for_tuple!(8 => D1, D2, D3, D4, D5, D6, D7, D8);
+impl_dyn_datapoint_for_tuple!(9 => D1, D2, D3, D4, D5, D6, D7, D8, D9);
+impl_dyn_datapoint_for_tuple!(10 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10);
+impl_dyn_datapoint_for_tuple!(11 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11);
+impl_dyn_datapoint_for_tuple!(12 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12);
+impl_dyn_datapoint_for_tuple!(13 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12, D13);
+impl_dyn_datapoint_for_tuple!(14 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12, D13, D14);
+impl_dyn_datapoint_for_tuple!(15 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12, D13, D14, D15);
+impl_dyn_datapoint_for_tuple!(16 => D1, D2, D3, D4, D5, D6, D7, D8, D9, D10, D11, D12, D13, D14, D15, D16);
+
+/**
+Maps are recorded by key: In the order of the labels if the dataset has labels
+(in which case the keys must be the same as the labels), otherwise in the order of the keys.
+*/
+impl<K, V, S> DynDatapoint for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq + Ord,
+    V: Field,
+    S: BuildHasher,
+{
+    fn record_dyn(&self) -> Vec<String> {
+        let mut entries: Vec<(&K, &V)> = self.iter().collect();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+        entries
+            .into_iter()
+            .map(|(_, v)| field::to_string(v))
+            .collect()
+    }
+
+    fn record_labelled(&self, labels: &[String]) -> Vec<String> {
+        let get = |label: &String| self.get(label.as_str()).map(field::to_string);
+        labels
+            .iter()
+            .map(|label| get(label).unwrap_or_default())
+            .collect()
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        if let Some(labels) = labels {
+            write_by_label(labels, |label| self.get(label), record);
+        } else {
+            let mut entries: Vec<(&K, &V)> = self.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            for (_, value) in entries {
+                record.push(value);
+            }
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.len()
+    }
+
+    fn keys(&self) -> Option<Vec<&str>> {
+        let mut keys: Vec<&str> = HashMap::keys(self).map(Borrow::borrow).collect();
+        keys.sort_unstable();
+        Some(keys)
+    }
+}
+
+/**
+Maps are recorded by key: In the order of the labels if the dataset has labels
+(in which case the keys must be the same as the labels), otherwise in the order of the keys.
+*/
+impl<K, V> DynDatapoint for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+    V: Field,
+{
+    fn record_dyn(&self) -> Vec<String> {
+        self.values().map(field::to_string).collect()
+    }
+
+    fn record_labelled(&self, labels: &[String]) -> Vec<String> {
+        let get = |label: &String| self.get(label.as_str()).map(field::to_string);
+        labels
+            .iter()
+            .map(|label| get(label).unwrap_or_default())
+            .collect()
+    }
+
+    fn write_dyn(&self, labels: Option<&[String]>, record: &mut RecordBuf) {
+        if let Some(labels) = labels {
+            write_by_label(labels, |label| self.get(label), record);
+        } else {
+            for value in self.values() {
+                record.push(value);
+            }
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.len()
+    }
+
+    fn keys(&self) -> Option<Vec<&str>> {
+        Some(BTreeMap::keys(self).map(Borrow::borrow).collect())
+    }
+}
+
+/// Write the value of each label, or a missing value for labels without a value
+fn write_by_label<'a, V, F>(labels: &[String], get: F, record: &mut RecordBuf)
+where
+    V: Field + 'a,
+    F: Fn(&str) -> Option<&'a V>,
+{
+    for label in labels {
+        match get(label) {
+            Some(value) => record.push(value),
+            None => record.push_missing(),
+        }
+    }
+}
+
+/**
+A dataset where the number of columns is only known at runtime, e.g. one column per sensor given in a config file.
+
+The number of columns is set by the labels or by the first datapoint,
+and every datapoint pushed afterwards must have the same number of elements.
+
+```
+use delfi::DynDataset;
+
+let sensors = ["left", "right", "top"];
+let mut dataset = DynDataset::new().with_labels(sensors).unwrap();
+dataset.push(vec![0.5, 1.0, 2.0]).unwrap();
+dataset.push(vec![0.25, 1.5, 3.0]).unwrap();
+assert!(dataset.push(vec![1.0]).is_err());
+
+dataset.save("./resources/data/examples/dyn.csv").unwrap();
+```
+*/
+#[derive(Debug, Clone)]
+pub struct DynDataset<Data: DynDatapoint> {
+    labels: Option<Vec<String>>,
+    width: Option<usize>,
+    data: Vec<Data>,
+    float_formats: FloatFormats,
+}
+
+impl<Data: DynDatapoint> DynDataset<Data> {
+    /**
+    Function for creating new (empty) dataset
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            labels: None,
+            width: None,
+            data: Vec::new(),
+            float_formats: FloatFormats::default(),
+        }
+    }
+
+    /**
+    Create a dataset from an iterator over datapoints
+
+    # Errors
+    Returns an error if the datapoints differ in number of elements
+    */
+    pub fn from_datapoints<IntoIter>(rows: IntoIter) -> Result<Self, Error>
+    where
+        IntoIter: IntoIterator<Item = Data>,
+    {
+        let mut dataset = Self::new();
+        for datapoint in rows {
+            dataset.push(datapoint)?;
+        }
+        Ok(dataset)
+    }
+
+    /**
+    Push a new row to the dataset
+
+    # Errors
+    Returns an error if the number of elements in the datapoint
+    differs from the number of columns of the dataset,
+    or if the keys of the datapoint (such as a map) differ from the labels
+    */
+    pub fn push(&mut self, datapoint: Data) -> Result<(), Error> {
+        if let Some(labels) = &self.labels {
+            check_keys(&datapoint, labels, self.n_rows())?;
+        }
+        let found = datapoint.width();
+        match self.width {
+            Some(expected) if expected != found => {
+                return Err(Error::Shape {
+                    path: None,
+                    row: self.n_rows(),
+                    expected,
+                    found,
+                });
+            }
+            Some(_) => {}
+            None => self.width = Some(found),
+        }
+        self.data.push(datapoint);
+        Ok(())
+    }
+
+    /**
+    Get current number of rows in dataset, which is equal to the number of datapoints, plus 1 if there is a header row
+    */
+    #[must_use]
+    pub fn n_rows(&self) -> usize {
+        match self.labels {
+            Some(_) => self.data.len() + 1,
+            None => self.data.len(),
+        }
+    }
+
+    /**
+    Get current number of datapoints in dataset
+    */
+    #[must_use]
+    pub fn n_datapoints(&self) -> usize {
+        self.data.len()
+    }
+
+    /**
+    Get current number of columns in dataset, which is 0 if there are no labels or datapoints yet
+    */
+    #[must_use]
+    pub fn n_columns(&self) -> usize {
+        self.width.unwrap_or(0)
+    }
+
+    /**
+    Get current labels
+    */
+    #[must_use]
+    pub fn get_labels(&self) -> Option<&[String]> {
+        self.labels.as_deref()
+    }
+
+    /**
+    Set labels for the given dataset
+
+    # Errors
+    Returns an error if the number of labels differs from the number of columns of the dataset,
+    or if the keys of any datapoint (such as a map) differ from the labels
+    */
+    pub fn set_labels<Labels, Label>(&mut self, labels: Labels) -> Result<(), Error>
+    where
+        Labels: IntoIterator<Item = Label>,
+        Label: Into<String>,
+    {
+        let labels: Vec<String> = labels.into_iter().map(Into::into).collect();
+        for (i, datapoint) in self.data.iter().enumerate() {
+            check_keys(datapoint, &labels, i + 1)?;
+        }
+        match self.width {
+            Some(expected) if !self.data.is_empty() && expected != labels.len() => {
+                Err(Error::Shape {
+                    path: None,
+                    row: 0,
+                    expected,
+                    found: labels.len(),
+                })
+            }
+            _ => {
+                self.width = Some(labels.len());
+                self.labels = Some(labels);
+                Ok(())
+            }
+        }
+    }
+
+    /**
+    Take dataset, set labels, and return dataset. Useful when constructing datasets.
+
+    # Errors
+    Returns an error if the number of labels differs from the number of columns of the dataset
+    */
+    pub fn with_labels<Labels, Label>(mut self, labels: Labels) -> Result<Self, Error>
+    where
+        Labels: IntoIterator<Item = Label>,
+        Label: Into<String>,
+    {
+        self.set_labels(labels)?;
+        Ok(self)
+    }
+
+    /**
+    Turn labels off for the given dataset
+    */
+    pub fn clear_labels(&mut self) {
+        self.labels = None;
+        if self.data.is_empty() {
+            self.width = None;
+        }
+    }
+
+    /**
+    Get the float format used for the given column
+    */
+    #[must_use]
+    pub fn get_float_format(&self, column: usize) -> FloatFormat {
+        self.float_formats.get(column)
+    }
+
+    /**
+    Set the float format used for all columns without a format of their own, see [`FloatFormat`]
+    */
+    pub fn set_float_format(&mut self, format: FloatFormat) {
+        self.float_formats.set_default(format);
+    }
+
+    /**
+    Take dataset, set the float format used for all columns without a format of their own, and return dataset
+    */
+    #[must_use]
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.set_float_format(format);
+        self
+    }
+
+    /**
+    Set the float format used for the given column, overriding the format of the dataset
+    */
+    pub fn set_column_float_format(&mut self, column: usize, format: FloatFormat) {
+        self.float_formats.set_column(column, format);
+    }
+
+    /**
+    Take dataset, set the float format used for the given column, and return dataset
+
+    ```
+    use delfi::{DynDataset, FloatFormat};
+
+    let dataset = DynDataset::from_datapoints([vec![1.0 / 3.0, 1.0 / 3.0]])
+        .unwrap()
+        .with_column_float_format(1, FloatFormat::Scientific(2));
+    assert_eq!(dataset.to_csv_string().unwrap(), "0.3333333333333333,3.33e-1\n");
+    ```
+    */
+    #[must_use]
+    pub fn with_column_float_format(mut self, column: usize, format: FloatFormat) -> Self {
+        self.set_column_float_format(column, format);
+        self
+    }
+
+    /**
+    Saves a dataset to a given file. The filepath must be valid.
+    Accepts anything path-like.
+
+    # Errors
+    Returns an error if the file could not be created or written to
+    */
+    pub fn save<P: AsRef<Path>>(&self, filepath: P) -> Result<(), Error> {
+        self.save_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Saves a dataset to a given file, using the given options for the csv dialect.
+    The filepath must be valid. Accepts anything path-like.
+
+    # Errors
+    Returns an error if the file could not be created or written to
+    */
+    pub fn save_with<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        output::save(filepath.as_ref(), options, |file| {
+            self.write_to_with(file, options)
+        })
+    }
+
+    /**
+    Writes a dataset as csv to anything implementing [`std::io::Write`]
+
+    # Errors
+    Returns an error if writing to the writer failed
+    */
+    pub fn write_to<W: io::Write>(&self, writer: W) -> Result<(), Error> {
+        self.write_to_with(writer, &SaveOptions::default())
+    }
+
+    /**
+    Writes a dataset as csv to anything implementing [`std::io::Write`],
+    using the given options for the csv dialect
+
+    # Errors
+    Returns an error if writing to the writer failed
+    */
+    pub fn write_to_with<W: io::Write>(
+        &self,
+        writer: W,
+        options: &SaveOptions,
+    ) -> Result<(), Error> {
+        let mut writer = output::CsvWriter::new(writer, options);
+        writer.float_formats_mut().clone_from(&self.float_formats);
+        if let Some(labels) = &self.labels {
+            writer.write_labels(labels)?;
+        }
+        let labels = self.labels.as_deref();
+        for datapoint in &self.data {
+            writer.write_fields(|record| datapoint.write_dyn(labels, record))?;
+        }
+        writer.flush()
+    }
+
+    /**
+    Writes a dataset as csv to a string
+
+    # Errors
+    Returns an error if any of the datapoints could not be written
+    */
+    pub fn to_csv_string(&self) -> Result<String, Error> {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer)?;
+        String::from_utf8(buffer)
+            .map_err(|e| Error::from(io::Error::new(io::ErrorKind::InvalidData, e)))
+    }
+}
+
+/// Check that the keys of a datapoint (if it is recorded by key) are the same as the labels
+fn check_keys<Data: DynDatapoint>(
+    datapoint: &Data,
+    labels: &[String],
+    row: usize,
+) -> Result<(), Error> {
+    let Some(keys) = datapoint.keys() else {
+        return Ok(());
+    };
+    let missing: Vec<String> = labels
+        .iter()
+        .filter(|label| !keys.contains(&label.as_str()))
+        .cloned()
+        .collect();
+    let unexpected: Vec<String> = keys
+        .iter()
+        .filter(|key| !labels.iter().any(|label| label == *key))
+        .map(|key| (*key).to_owned())
+        .collect();
+    if missing.is_empty() && unexpected.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Keys {
+            row,
+            missing,
+            unexpected,
+        })
+    }
+}
+
+/**
+Default is equivalent to new
+*/
+impl<Data: DynDatapoint> Default for DynDataset<Data> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+Converting a dataset moves its datapoints, without recording or copying them,
+and keeps its labels and float formats
+
+```
+use delfi::{Dataset, DynDataset};
+
+let dataset = Dataset::from_columns([[0, 1, 2], [2, 3, 5]]).with_labels(["time", "length"]);
+let dataset = DynDataset::from(dataset);
+assert_eq!(dataset.n_columns(), 2);
+```
+*/
+impl<const COLS: usize, Data> From<Dataset<COLS, Data>> for DynDataset<Data>
+where
+    Data: Datapoint<COLS> + DynDatapoint,
+{
+    fn from(dataset: Dataset<COLS, Data>) -> Self {
+        let (labels, data, float_formats) = dataset.into_parts();
+        Self {
+            labels: labels.map(Vec::from),
+            width: Some(COLS),
+            data,
+            float_formats,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NonFinite;
+
+    #[test]
+    fn shape() {
+        let mut dataset = DynDataset::new();
+        assert_eq!(dataset.n_columns(), 0);
+        dataset.push(vec![1, 2, 3]).unwrap();
+        assert_eq!(dataset.n_columns(), 3);
+
+        let error = dataset.push(vec![4, 5]).unwrap_err();
+        assert_eq!(error.row(), Some(1));
+        assert_eq!(dataset.n_datapoints(), 1);
+
+        assert!(dataset.set_labels(["a", "b"]).is_err());
+        dataset.set_labels(["a", "b", "c"]).unwrap();
+        assert_eq!(dataset.to_csv_string().unwrap(), "a,b,c\n1,2,3\n");
+    }
+
+    #[test]
+    fn slices() {
+        let values = [1.5, 2.5, 3.5, 4.5];
+        let dataset = DynDataset::from_datapoints(values.chunks(2)).unwrap();
+        assert_eq!(dataset.to_csv_string().unwrap(), "1.5,2.5\n3.5,4.5\n");
+    }
+
+    #[test]
+    fn maps() {
+        let mut dataset = DynDataset::new().with_labels(["y", "x"]).unwrap();
+        dataset.push(HashMap::from([("x", 1), ("y", 2)])).unwrap();
+        dataset.push(HashMap::from([("y", 4), ("x", 3)])).unwrap();
+        assert_eq!(dataset.to_csv_string().unwrap(), "y,x\n2,1\n4,3\n");
+
+        let dataset = DynDataset::from_datapoints([BTreeMap::from([("b", 1), ("a", 2)])]).unwrap();
+        assert_eq!(dataset.to_csv_string().unwrap(), "2,1\n");
+    }
+
+    #[test]
+    fn map_keys() {
+        let mut dataset = DynDataset::new().with_labels(["y", "x"]).unwrap();
+        dataset.push(HashMap::from([("x", 1), ("y", 2)])).unwrap();
+
+        // A missing key, as well as an unexpected key of the same width
+        match dataset.push(HashMap::from([("x", 3), ("z", 4)])) {
+            Err(Error::Keys {
+                row,
+                missing,
+                unexpected,
+            }) => {
+                assert_eq!(row, 2);
+                assert_eq!(missing, ["y"]);
+                assert_eq!(unexpected, ["z"]);
+            }
+            _ => panic!("Expected a key error"),
+        }
+
+        // A missing key only
+        match dataset.push(HashMap::from([("x", 5)])) {
+            Err(Error::Keys {
+                missing,
+                unexpected,
+                ..
+            }) => {
+                assert_eq!(missing, ["y"]);
+                assert!(unexpected.is_empty());
+            }
+            _ => panic!("Expected a key error"),
+        }
+        assert_eq!(dataset.n_datapoints(), 1);
+
+        // Labels set after the datapoints are pushed are checked as well
+        let mut dataset =
+            DynDataset::from_datapoints([BTreeMap::from([("a", 1), ("b", 2)])]).unwrap();
+        let error = dataset.set_labels(["a", "c"]).unwrap_err();
+        assert!(matches!(error, Error::Keys { row: 1, .. }));
+        assert_eq!(dataset.get_labels(), None);
+        dataset.set_labels(["b", "a"]).unwrap();
+        assert_eq!(dataset.to_csv_string().unwrap(), "b,a\n2,1\n");
+    }
+
+    #[test]
+    fn missing_values() {
+        let options = SaveOptions::new()
+            .with_missing("NA")
+            .with_non_finite(NonFinite::Replace);
+        let dataset =
+            DynDataset::from_datapoints([vec![Some(1.5), None], vec![Some(f64::NAN), Some(2.0)]])
+                .unwrap();
+        let mut output = Vec::new();
+        dataset.write_to_with(&mut output, &options).unwrap();
+        assert_eq!(output, b"1.5,NA\nNA,2\n");
+
+        let options = options.with_non_finite(NonFinite::Reject);
+        let error = dataset.write_to_with(Vec::new(), &options).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::NonFinite {
+                row: 1,
+                column: 0,
+                ..
+            }
+        ));
+
+        let mut dataset = DynDataset::new().with_labels(["x", "y"]).unwrap();
+        dataset
+            .push(BTreeMap::from([("x", None), ("y", Some(1))]))
+            .unwrap();
+        let mut output = Vec::new();
+        dataset.write_to_with(&mut output, &options).unwrap();
+        assert_eq!(output, b"x,y\nNA,1\n");
+    }
+
+    #[test]
+    fn from_dataset() {
+        let dataset = Dataset::from_datapoints([(1, "one"), (2, "two")]).with_labels(["n", "name"]);
+        let expected = dataset.to_csv_string().unwrap();
+        let dataset = DynDataset::from(dataset);
+        assert_eq!(dataset.n_columns(), 2);
+        assert_eq!(dataset.to_csv_string().unwrap(), expected);
+    }
+
+    #[test]
+    fn from_formatted_dataset() {
+        let dataset = Dataset::from_datapoints([[1.0 / 3.0, 0.5], [2.0, 1.0 / 8.0]])
+            .with_float_format(FloatFormat::Fixed(2))
+            .with_column_float_format(1, FloatFormat::Scientific(1));
+        let expected = "0.33,5.0e-1\n2.00,1.2e-1\n";
+        assert_eq!(dataset.to_csv_string().unwrap(), expected);
+
+        let mut dataset = DynDataset::from(dataset);
+        assert_eq!(dataset.get_float_format(0), FloatFormat::Fixed(2));
+        assert_eq!(dataset.to_csv_string().unwrap(), expected);
+
+        dataset.set_float_format(FloatFormat::Significant(1));
+        assert_eq!(dataset.to_csv_string().unwrap(), "0.3,5.0e-1\n2,1.2e-1\n");
+    }
+}
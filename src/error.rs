@@ -0,0 +1,228 @@
+/*!
+The [`Error`] type returned by fallible operations in this crate
+*/
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::from_record::ParseError;
+
+/**
+Error returned when reading or writing a dataset fails.
+
+Rows are counted from the start of the csv-data, including the header row if there is one.
+The path is set whenever the error occurred while reading or writing a file.
+
+```
+use delfi::{Dataset, Error};
+
+let result: Result<Dataset<2, [u8; 2]>, _> = Dataset::load("./does/not/exist.csv");
+match result {
+    Err(Error::Io { path, .. }) => assert!(path.is_some()),
+    _ => panic!("Expected an io error"),
+}
+```
+*/
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading from or writing to a file or writer failed
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    /// The csv-data could not be read or written
+    Csv {
+        path: Option<PathBuf>,
+        row: Option<usize>,
+        source: csv::Error,
+    },
+    /// A field could not be parsed, see [`ParseError`] for the column, label and text of the field
+    Parse {
+        path: Option<PathBuf>,
+        row: usize,
+        source: ParseError,
+    },
+    /// A row did not have the expected number of fields
+    Shape {
+        path: Option<PathBuf>,
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The columns used to construct a dataset differ in length
+    ColumnLengths { lengths: Vec<usize> },
+    /// A non-finite float was written while using [`NonFinite::Reject`](crate::NonFinite::Reject)
+    NonFinite {
+        path: Option<PathBuf>,
+        row: usize,
+        column: usize,
+        value: String,
+    },
+    /// The labels did not match the expected labels
+    Labels {
+        path: Option<PathBuf>,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// The keys of a datapoint (such as a map) did not match the labels of the dataset
+    Keys {
+        row: usize,
+        missing: Vec<String>,
+        unexpected: Vec<String>,
+    },
+}
+
+impl Error {
+    /**
+    The path of the file being read or written when the error occurred, if any
+    */
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Io { path, .. }
+            | Self::Csv { path, .. }
+            | Self::Parse { path, .. }
+            | Self::Shape { path, .. }
+            | Self::NonFinite { path, .. }
+            | Self::Labels { path, .. } => path.as_deref(),
+            Self::ColumnLengths { .. } | Self::Keys { .. } => None,
+        }
+    }
+
+    /**
+    The row at which the error occurred, if known
+    */
+    #[must_use]
+    pub fn row(&self) -> Option<usize> {
+        match self {
+            Self::Io { .. } | Self::ColumnLengths { .. } | Self::Labels { .. } => None,
+            Self::Csv { row, .. } => *row,
+            Self::Parse { row, .. }
+            | Self::Shape { row, .. }
+            | Self::NonFinite { row, .. }
+            | Self::Keys { row, .. } => Some(*row),
+        }
+    }
+
+    /// Set the path of the error, unless it is already set
+    pub(crate) fn at_path(mut self, filepath: &Path) -> Self {
+        match &mut self {
+            Self::Io { path, .. }
+            | Self::Csv { path, .. }
+            | Self::Parse { path, .. }
+            | Self::Shape { path, .. }
+            | Self::NonFinite { path, .. }
+            | Self::Labels { path, .. } => {
+                path.get_or_insert_with(|| filepath.to_owned());
+            }
+            Self::ColumnLengths { .. } | Self::Keys { .. } => {}
+        }
+        self
+    }
+
+    /// Create a csv error for the given row
+    pub(crate) fn csv_at_row(row: usize) -> impl FnOnce(csv::Error) -> Self {
+        move |source| Self::Csv {
+            path: None,
+            row: Some(row),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { source, .. } => write!(f, "{source}")?,
+            Self::Csv { row, source, .. } => match row {
+                Some(row) => write!(f, "row {row}: {source}")?,
+                None => write!(f, "{source}")?,
+            },
+            Self::Parse { row, source, .. } => write!(f, "row {row}: {source}")?,
+            Self::Shape {
+                row,
+                expected,
+                found,
+                ..
+            } => write!(f, "row {row}: expected {expected} fields, found {found}")?,
+            Self::NonFinite {
+                row, column, value, ..
+            } => write!(f, "row {row}: non-finite value {value} in column {column}")?,
+            Self::ColumnLengths { lengths } => {
+                write!(f, "columns differ in length, found lengths {lengths:?}")?;
+            }
+            Self::Labels {
+                expected, found, ..
+            } => write!(f, "expected labels {expected:?}, found {found:?}")?,
+            Self::Keys {
+                row,
+                missing,
+                unexpected,
+            } => write!(
+                f,
+                "row {row}: keys do not match the labels, missing {missing:?}, unexpected {unexpected:?}"
+            )?,
+        }
+        if let Some(path) = self.path() {
+            write!(f, " (in {})", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Csv { source, .. } => Some(source),
+            Self::Parse { source, .. } => Some(source),
+            Self::Shape { .. }
+            | Self::NonFinite { .. }
+            | Self::ColumnLengths { .. }
+            | Self::Labels { .. }
+            | Self::Keys { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::Io { path: None, source }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(source: csv::Error) -> Self {
+        Self::Csv {
+            path: None,
+            row: None,
+            source,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let error = Error::Shape {
+            path: None,
+            row: 3,
+            expected: 2,
+            found: 1,
+        };
+        assert_eq!(error.to_string(), "row 3: expected 2 fields, found 1");
+
+        let error = error.at_path(Path::new("data.csv"));
+        assert_eq!(error.path(), Some(Path::new("data.csv")));
+        assert_eq!(error.row(), Some(3));
+        assert_eq!(
+            error.to_string(),
+            "row 3: expected 2 fields, found 1 (in data.csv)"
+        );
+    }
+}
@@ -0,0 +1,556 @@
+/*!
+Buffer-based recording of datapoints using the [`Field`] trait and the [`RecordBuf`] struct
+*/
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::format::{self, FloatFormat, FloatFormats};
+use crate::options::{NonFinite, SaveOptions};
+use crate::Datapoint;
+
+/**
+A single element of a datapoint, which can write itself as a field of a record.
+
+This is implemented for numbers, booleans, characters and strings, as well as for options of these,
+where `None` is written as a missing value (see [`SaveOptions::with_missing`]). Numbers are written without
+allocating: Integers as by their [`Display`](std::fmt::Display) implementation, and floats as the
+shortest text which parses back to the same value (unless another [`FloatFormat`] is used).
+This is the same text as `Display`, except for rare values where another final digit is equally short.
+Types which only implement `Display` can be wrapped in [`Displayed`], or implement this trait
+using [`RecordBuf::push_display`]:
+
+```
+use std::fmt;
+use delfi::field::{Field, RecordBuf};
+
+struct Celsius(f64);
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} C", self.0)
+    }
+}
+
+impl Field for Celsius {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_display(self);
+    }
+}
+
+let mut record = RecordBuf::new();
+record.push(&Celsius(21.5));
+assert_eq!(record.get(0), Some("21.5 C"));
+```
+*/
+pub trait Field {
+    /**
+    Write the element as a single field at the end of the record
+    */
+    fn write_field(&self, record: &mut RecordBuf);
+}
+
+/**
+A reusable buffer for the fields of a single record.
+
+Datapoints write their fields into the buffer using [`Datapoint::write_record`], which avoids
+allocating a string for every field. The buffer keeps its capacity when cleared,
+so it can be reused for every row of a dataset.
+
+```
+use delfi::field::RecordBuf;
+use delfi::Datapoint;
+
+let mut record = RecordBuf::new();
+(1, 0.5, "x").write_record(&mut record);
+assert_eq!(record.len(), 3);
+assert_eq!(record.get(1), Some("0.5"));
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct RecordBuf {
+    record: csv::ByteRecord,
+    scratch: String,
+    plain: String,
+    float_formats: FloatFormats,
+    missing: String,
+    non_finite: NonFinite,
+    /// The first column with a non-finite float, when using `NonFinite::Reject`
+    rejected: Option<usize>,
+    /// The columns holding missing values or bools, which are not written as strings in JSON
+    literals: Vec<(usize, Literal)>,
+}
+
+/// A field which is written as a JSON literal rather than as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Literal {
+    Missing,
+    Bool,
+}
+
+impl RecordBuf {
+    /**
+    Create a new, empty buffer
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Write a field at the end of the record
+    */
+    pub fn push<F: Field + ?Sized>(&mut self, field: &F) {
+        field.write_field(self);
+    }
+
+    /**
+    Write a string as a field at the end of the record
+    */
+    pub fn push_str(&mut self, field: &str) {
+        self.record.push_field(field.as_bytes());
+    }
+
+    /**
+    Write anything implementing [`Display`](std::fmt::Display) as a field at the end of the record,
+    formatting it into a reused buffer rather than a new string
+    */
+    pub fn push_display<T: fmt::Display + ?Sized>(&mut self, field: &T) {
+        self.scratch.clear();
+        // Formatting into a string only fails if the Display implementation itself fails,
+        // in which case the field is left with whatever was written
+        let _ = write!(self.scratch, "{field}");
+        self.record.push_field(self.scratch.as_bytes());
+    }
+
+    /**
+    Get the field at the given column, if any
+    */
+    #[must_use]
+    pub fn get(&self, column: usize) -> Option<&str> {
+        // Fields are only ever written from strings, so they are valid utf-8
+        self.record
+            .get(column)
+            .and_then(|field| std::str::from_utf8(field).ok())
+    }
+
+    /**
+    Number of fields written to the record
+    */
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.record.len()
+    }
+
+    /**
+    Write a missing value as a field at the end of the record,
+    using the text set by [`SaveOptions::with_missing`] (empty by default)
+    */
+    pub fn push_missing(&mut self) {
+        self.literals.push((self.len(), Literal::Missing));
+        self.record.push_field(self.missing.as_bytes());
+    }
+
+    /**
+    Whether no fields have been written to the record
+    */
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.record.is_empty()
+    }
+
+    /**
+    Remove all fields from the record, keeping the allocated memory
+    */
+    pub fn clear(&mut self) {
+        self.record.clear();
+        self.rejected = None;
+        self.literals.clear();
+    }
+
+    /// The formats used for floats in each column
+    pub(crate) fn float_formats_mut(&mut self) -> &mut FloatFormats {
+        &mut self.float_formats
+    }
+
+    /// Use the missing value and non-finite policy of the options
+    pub(crate) fn set_missing(&mut self, options: &SaveOptions) {
+        self.missing.clear();
+        self.missing.push_str(options.missing());
+        self.non_finite = options.non_finite();
+    }
+
+    /// The first column with a non-finite float which should be rejected, if any
+    pub(crate) fn rejected(&self) -> Option<usize> {
+        self.rejected
+    }
+
+    /// Whether the field at the given column is a missing value or a bool, if it is either
+    pub(crate) fn literal(&self, column: usize) -> Option<Literal> {
+        self.literals
+            .iter()
+            .find(|&&(literal_column, _)| literal_column == column)
+            .map(|&(_, literal)| literal)
+    }
+
+    /// The fields written so far, as used by the csv writer
+    pub(crate) fn as_byte_record(&self) -> &csv::ByteRecord {
+        &self.record
+    }
+
+    /// Write a bool as `true` or `false`
+    fn push_bool(&mut self, value: bool) {
+        self.literals.push((self.len(), Literal::Bool));
+        self.push_str(if value { "true" } else { "false" });
+    }
+
+    /// Write an integer without allocating
+    fn push_int<I: itoa::Integer>(&mut self, value: I) {
+        let mut buffer = itoa::Buffer::new();
+        self.push_str(buffer.format(value));
+    }
+
+    /// Write a float without allocating, using the float format of the column being written
+    fn push_float<F: Float>(&mut self, value: F) {
+        if !value.is_finite() {
+            return match self.non_finite {
+                NonFinite::Keep => self.push_display(&value),
+                NonFinite::Replace => self.push_missing(),
+                NonFinite::Reject => {
+                    self.rejected.get_or_insert(self.len());
+                    self.push_display(&value);
+                }
+            };
+        }
+        match self.float_formats.get(self.len()) {
+            FloatFormat::RoundTrip => {
+                let mut buffer = ryu::Buffer::new();
+                let text = buffer.format_finite(value);
+                if text.contains('e') {
+                    // Fall back to Display for very large and small values, which never uses scientific notation
+                    self.push_display(&value);
+                } else {
+                    self.push_str(text.strip_suffix(".0").unwrap_or(text));
+                }
+            }
+            FloatFormat::Fixed(decimals) => self.push_display(&format_args!("{value:.decimals$}")),
+            FloatFormat::Significant(digits) => {
+                let decimals = digits.max(1) - 1;
+                self.scratch.clear();
+                let _ = write!(self.scratch, "{value:.decimals$e}");
+                format::expand_exponent(&self.scratch, &mut self.plain);
+                self.record.push_field(self.plain.as_bytes());
+            }
+            FloatFormat::Scientific(decimals) => {
+                self.push_display(&format_args!("{value:.decimals$e}"));
+            }
+        }
+    }
+}
+
+/// The floating point types which can be written as fields
+trait Float: ryu::Float + fmt::Display + fmt::LowerExp {
+    fn is_finite(self) -> bool;
+}
+
+impl Float for f32 {
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl Float for f64 {
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+/**
+Record a datapoint as strings, using its [`write_record`](Datapoint::write_record) implementation.
+
+This can be used to implement [`Datapoint::record`] for datapoints which implement the buffer-based method.
+*/
+pub fn record<const N: usize, D: Datapoint<N> + ?Sized>(datapoint: &D) -> [String; N] {
+    let mut record = RecordBuf::new();
+    datapoint.write_record(&mut record);
+    std::array::from_fn(|column| record.get(column).unwrap_or_default().to_owned())
+}
+
+/// Write a single element as a string
+pub(crate) fn to_string<F: Field + ?Sized>(element: &F) -> String {
+    let mut record = RecordBuf::new();
+    record.push(element);
+    record.get(0).unwrap_or_default().to_owned()
+}
+
+/// Implement `Field` for integer types
+macro_rules! impl_field_for_int {
+    ($($int:ty),+) => {
+        $(impl Field for $int {
+            fn write_field(&self, record: &mut RecordBuf) {
+                record.push_int(*self);
+            }
+        })+
+    };
+}
+
+impl_field_for_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl Field for f32 {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_float(*self);
+    }
+}
+
+impl Field for f64 {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_float(*self);
+    }
+}
+
+impl Field for bool {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_bool(*self);
+    }
+}
+
+impl Field for char {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_str(self.encode_utf8(&mut [0; 4]));
+    }
+}
+
+impl Field for str {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_str(self);
+    }
+}
+
+impl Field for String {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_str(self);
+    }
+}
+
+impl Field for Cow<'_, str> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_str(self);
+    }
+}
+
+/// Missing values are written using [`RecordBuf::push_missing`]
+impl<T: Field> Field for Option<T> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        match self {
+            Some(value) => value.write_field(record),
+            None => record.push_missing(),
+        }
+    }
+}
+
+impl<T: Field + ?Sized> Field for &T {
+    fn write_field(&self, record: &mut RecordBuf) {
+        (**self).write_field(record);
+    }
+}
+
+impl<T: Field + ?Sized> Field for Box<T> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        (**self).write_field(record);
+    }
+}
+
+impl<T: Field + ?Sized> Field for Rc<T> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        (**self).write_field(record);
+    }
+}
+
+impl<T: Field + ?Sized> Field for Arc<T> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        (**self).write_field(record);
+    }
+}
+
+/**
+A wrapper writing anything implementing [`Display`](fmt::Display) as a field, using [`RecordBuf::push_display`].
+
+Arrays, tuples and columns require their elements to implement [`Field`],
+so types which only implement `Display` (such as types from other crates) are used by wrapping them.
+Derived datapoints write fields which only implement `Display` without any wrapping.
+
+```
+use std::net::Ipv4Addr;
+use delfi::field::Displayed;
+use delfi::Dataset;
+
+let hosts = [Ipv4Addr::LOCALHOST, Ipv4Addr::new(10, 0, 0, 1)];
+let ds = Dataset::from_columns([hosts.map(Displayed)]);
+assert_eq!(ds.to_csv_string().unwrap(), "127.0.0.1\n10.0.0.1\n");
+
+let ds = Dataset::from_datapoints([(Displayed(hosts[0]), 80)]);
+assert_eq!(ds.to_csv_string().unwrap(), "127.0.0.1,80\n");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Displayed<T>(pub T);
+
+impl<T: fmt::Display> Field for Displayed<T> {
+    fn write_field(&self, record: &mut RecordBuf) {
+        record.push_display(&self.0);
+    }
+}
+
+/**
+Writing the fields of derived datapoints using [`Field`] if the type implements it,
+and [`Display`](fmt::Display) otherwise.
+
+The choice is made by method resolution: For `(&Dispatch(value)).write_dispatch(record)`
+the method taking `&Dispatch` (requiring `Field`) is found before the method taking `&&Dispatch`
+(requiring `Display`), which is only used if the first one does not apply.
+*/
+#[doc(hidden)]
+pub mod dispatch {
+    use std::fmt;
+
+    use super::{Field, RecordBuf};
+
+    /// A reference to the value of a field
+    pub struct Dispatch<'a, T: ?Sized>(pub &'a T);
+
+    /// Write the field using its `Field` implementation
+    pub trait ViaField {
+        fn write_dispatch(&self, record: &mut RecordBuf);
+    }
+
+    impl<T: Field + ?Sized> ViaField for Dispatch<'_, T> {
+        fn write_dispatch(&self, record: &mut RecordBuf) {
+            record.push(self.0);
+        }
+    }
+
+    /// Write the field using its `Display` implementation
+    pub trait ViaDisplay {
+        fn write_dispatch(&self, record: &mut RecordBuf);
+    }
+
+    impl<T: fmt::Display + ?Sized> ViaDisplay for &Dispatch<'_, T> {
+        fn write_dispatch(&self, record: &mut RecordBuf) {
+            record.push_display(self.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<F: Field + ?Sized>(value: &F) -> String {
+        let mut record = RecordBuf::new();
+        record.push(value);
+        record.get(0).unwrap().to_owned()
+    }
+
+    #[test]
+    fn integers() {
+        for value in [0, 1, -1, 42, i64::MIN, i64::MAX] {
+            assert_eq!(field(&value), value.to_string());
+        }
+        assert_eq!(field(&u128::MAX), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn floats() {
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -2.5,
+            0.1 + 0.2,
+            1.0 / 3.0,
+            1e-7,
+            1.5e-5,
+            1e15,
+            1e16,
+            1.234_567_89e21,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for value in values {
+            assert_eq!(field(&value), value.to_string());
+        }
+        // Sweep through many orders of magnitude, checking that the text round-trips
+        let mut value = 1e-30_f64;
+        while value < 1e30 {
+            assert_eq!(
+                field(&value).parse::<f64>().unwrap().to_bits(),
+                value.to_bits()
+            );
+            assert_eq!(
+                field(&-value).parse::<f64>().unwrap().to_bits(),
+                (-value).to_bits()
+            );
+            #[allow(clippy::cast_possible_truncation)]
+            let single = value as f32;
+            assert_eq!(
+                field(&single).parse::<f32>().unwrap().to_bits(),
+                single.to_bits()
+            );
+            assert!(!field(&value).contains('e'));
+            value *= 7.3;
+        }
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(field("a,b"), "a,b");
+        assert_eq!(field(&String::from("x")), "x");
+        assert_eq!(field(&'é'), "é");
+        assert_eq!(field(&true), "true");
+        assert_eq!(field(&Box::new(3)), "3");
+    }
+
+    #[test]
+    fn reuse() {
+        let mut record = RecordBuf::new();
+        (1, "a").write_record(&mut record);
+        record.clear();
+        assert!(record.is_empty());
+        (2, "b").write_record(&mut record);
+        assert_eq!(record.len(), 2);
+        assert_eq!(record.get(0), Some("2"));
+        assert_eq!(record.get(2), None);
+    }
+
+    #[test]
+    fn missing() {
+        assert_eq!(field(&Some(1.5)), "1.5");
+        assert_eq!(field(&None::<f64>), "");
+
+        let options = SaveOptions::new()
+            .with_missing("NA")
+            .with_non_finite(NonFinite::Replace);
+        let mut record = RecordBuf::new();
+        record.set_missing(&options);
+        (None::<u8>, f64::NAN, Some(f32::INFINITY), 2.0).write_record(&mut record);
+        assert_eq!(record.get(0), Some("NA"));
+        assert_eq!(record.get(1), Some("NA"));
+        assert_eq!(record.get(2), Some("NA"));
+        assert_eq!(record.get(3), Some("2"));
+        assert_eq!(record.rejected(), None);
+
+        record.set_missing(&options.with_non_finite(NonFinite::Reject));
+        record.clear();
+        (1.0, f64::NEG_INFINITY, f64::NAN).write_record(&mut record);
+        assert_eq!(record.rejected(), Some(1));
+        assert_eq!(record.get(1), Some("-inf"));
+        record.clear();
+        assert_eq!(record.rejected(), None);
+    }
+}
@@ -0,0 +1,134 @@
+/*!
+Formatting of floating point fields using the [`FloatFormat`] enum
+*/
+
+/**
+How floating point numbers (`f32` and `f64`) are written to csv.
+
+The format can be set for all columns of a dataset, or for individual columns:
+```
+use delfi::{Dataset, FloatFormat};
+
+let t = [0.0, 0.1, 0.2];
+let x = [0.1 + 0.2, 1.0 / 3.0, 2.0 / 3.0];
+let dataset = Dataset::from_columns([t, x])
+    .with_labels(["time", "length"])
+    .with_float_format(FloatFormat::Significant(3))
+    .with_column_float_format(0, FloatFormat::Fixed(1));
+# dataset.save("./resources/data/examples/float-format.csv").unwrap();
+assert_eq!(dataset.to_csv_string().unwrap(), "time,length\n0.0,0.300\n0.1,0.333\n0.2,0.667\n");
+```
+
+Values which are not finite (`NaN` and infinities) are written the same way in every format.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The shortest text which parses back to the same value, e.g. `0.30000000000000004`
+    #[default]
+    RoundTrip,
+    /// A fixed number of decimals, e.g. `0.300` using 3 decimals
+    Fixed(usize),
+    /// A number of significant digits (at least one), e.g. `1230` or `0.00123` using 3 significant digits
+    Significant(usize),
+    /// Scientific notation with a number of decimals in the mantissa, e.g. `1.23e3` using 2 decimals
+    Scientific(usize),
+}
+
+/// The float format of each column, falling back to a default for columns without their own format
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct FloatFormats {
+    default: FloatFormat,
+    columns: Vec<Option<FloatFormat>>,
+}
+
+impl FloatFormats {
+    /// Get the format used for the given column
+    pub(crate) fn get(&self, column: usize) -> FloatFormat {
+        self.columns
+            .get(column)
+            .copied()
+            .flatten()
+            .unwrap_or(self.default)
+    }
+
+    /// Set the format used for columns without their own format
+    pub(crate) fn set_default(&mut self, format: FloatFormat) {
+        self.default = format;
+    }
+
+    /// Set the format used for the given column
+    pub(crate) fn set_column(&mut self, column: usize, format: FloatFormat) {
+        if self.columns.len() <= column {
+            self.columns.resize(column + 1, None);
+        }
+        self.columns[column] = Some(format);
+    }
+}
+
+/// Rewrite a finite number in scientific notation (as written by `LowerExp`) without the exponent
+pub(crate) fn expand_exponent(scientific: &str, plain: &mut String) {
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((scientific, "0"));
+    let exponent: isize = exponent.parse().unwrap_or(0);
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.chars().filter(|c| *c != '.');
+
+    plain.clear();
+    plain.push_str(sign);
+    match usize::try_from(exponent) {
+        // Negative exponent: All digits go after the decimal point
+        Err(_) => {
+            plain.push_str("0.");
+            plain.extend(std::iter::repeat_n('0', exponent.unsigned_abs() - 1));
+            plain.extend(digits);
+        }
+        // Non-negative exponent: The first `exponent + 1` digits go before the decimal point
+        Ok(exponent) => {
+            let n_digits = mantissa.len() - usize::from(mantissa.contains('.'));
+            for (i, digit) in digits.enumerate() {
+                if i == exponent + 1 {
+                    plain.push('.');
+                }
+                plain.push(digit);
+            }
+            plain.extend(std::iter::repeat_n(
+                '0',
+                (exponent + 1).saturating_sub(n_digits),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(value: f64, significant: usize) -> String {
+        let mut plain = String::new();
+        expand_exponent(&format!("{:.*e}", significant - 1, value), &mut plain);
+        plain
+    }
+
+    #[test]
+    fn significant() {
+        assert_eq!(expand(0.1 + 0.2, 3), "0.300");
+        assert_eq!(expand(123_456.0, 3), "123000");
+        assert_eq!(expand(-123_456.0, 7), "-123456.0");
+        assert_eq!(expand(0.000_123_456, 2), "0.00012");
+        assert_eq!(expand(9.996, 3), "10.0");
+        assert_eq!(expand(0.0, 1), "0");
+        assert_eq!(expand(5.0, 1), "5");
+    }
+
+    #[test]
+    fn columns() {
+        let mut formats = FloatFormats::default();
+        formats.set_default(FloatFormat::Fixed(2));
+        formats.set_column(2, FloatFormat::Scientific(1));
+        assert_eq!(formats.get(0), FloatFormat::Fixed(2));
+        assert_eq!(formats.get(2), FloatFormat::Scientific(1));
+        assert_eq!(formats.get(5), FloatFormat::Fixed(2));
+    }
+}
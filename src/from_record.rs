@@ -0,0 +1,218 @@
+/*!
+Implementations on the [FromRecord] trait
+*/
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::FromRecord;
+
+/**
+Error returned when a record could not be parsed into a datapoint
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    column: usize,
+    field: Option<String>,
+    text: String,
+    message: String,
+}
+
+impl ParseError {
+    /**
+    Create a new parse error for the given column, containing the text which failed to parse
+    */
+    pub fn new<E: fmt::Display>(column: usize, text: &str, error: E) -> Self {
+        Self {
+            column,
+            field: None,
+            text: text.to_owned(),
+            message: error.to_string(),
+        }
+    }
+
+    /**
+    Create a new parse error for a record which does not have the expected number of fields
+    */
+    #[must_use]
+    pub fn length(expected: usize, found: usize) -> Self {
+        Self {
+            column: expected.min(found),
+            field: None,
+            text: String::new(),
+            message: format!("expected {expected} fields, found {found}"),
+        }
+    }
+
+    /**
+    Take error, set the name of the field which failed to parse, and return error
+    */
+    #[must_use]
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_owned());
+        self
+    }
+
+    /**
+    Index of the column which failed to parse
+    */
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /**
+    Name of the field which failed to parse, if known
+    */
+    #[must_use]
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /**
+    The text which failed to parse
+    */
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to parse {:?} in column {}",
+            self.text, self.column
+        )?;
+        if let Some(field) = &self.field {
+            write!(f, " (field `{field}`)")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse<T>(column: usize, text: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    text.parse().map_err(|e| ParseError::new(column, text, e))
+}
+
+impl<const N: usize, Data> FromRecord<N> for [Data; N]
+where
+    Data: FromStr,
+    Data::Err: fmt::Display,
+{
+    fn from_record(record: [String; N]) -> Result<Self, ParseError> {
+        let parsed: Vec<Data> = record
+            .iter()
+            .enumerate()
+            .map(|(i, text)| parse(i, text))
+            .collect::<Result<_, _>>()?;
+        let found = parsed.len();
+        parsed.try_into().map_err(|_| ParseError::length(N, found))
+    }
+}
+
+/// Implement `FromRecord` for a tuple, given its arity and the type parameter, index and field name of each element
+macro_rules! impl_from_record_for_tuple {
+    ($n:literal => $($name:ident $index:tt $field:ident),+) => {
+        impl<$($name),+> FromRecord<$n> for ($($name,)+)
+        where
+            $($name: FromStr, $name::Err: fmt::Display,)+
+        {
+            fn from_record(record: [String; $n]) -> Result<Self, ParseError> {
+                let [$($field),+] = record;
+                Ok(($(parse($index, &$field)?,)+))
+            }
+        }
+    };
+}
+
+impl_from_record_for_tuple!(1 => D1 0 r1);
+impl_from_record_for_tuple!(2 => D1 0 r1, D2 1 r2);
+impl_from_record_for_tuple!(3 => D1 0 r1, D2 1 r2, D3 2 r3);
+impl_from_record_for_tuple!(4 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4);
+impl_from_record_for_tuple!(5 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5);
+impl_from_record_for_tuple!(6 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6);
+impl_from_record_for_tuple!(7 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7);
+impl_from_record_for_tuple!(8 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8);
+impl_from_record_for_tuple!(9 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9);
+impl_from_record_for_tuple!(10 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10);
+impl_from_record_for_tuple!(11 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11);
+impl_from_record_for_tuple!(12 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11, D12 11 r12);
+impl_from_record_for_tuple!(13 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11, D12 11 r12, D13 12 r13);
+impl_from_record_for_tuple!(14 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11, D12 11 r12, D13 12 r13, D14 13 r14);
+impl_from_record_for_tuple!(15 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11, D12 11 r12, D13 12 r13, D14 13 r14, D15 14 r15);
+impl_from_record_for_tuple!(16 => D1 0 r1, D2 1 r2, D3 2 r3, D4 3 r4, D5 4 r5, D6 5 r6, D7 6 r7, D8 7 r8, D9 8 r9, D10 9 r10, D11 10 r11, D12 11 r12, D13 12 r13, D14 13 r14, D15 14 r15, D16 15 r16);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record<const N: usize>(fields: [&str; N]) -> [String; N] {
+        fields.map(ToOwned::to_owned)
+    }
+
+    #[test]
+    fn array() {
+        let parsed = <[i32; 3]>::from_record(record(["0", "1", "2"])).unwrap();
+        assert_eq!(parsed, [0, 1, 2]);
+    }
+
+    #[test]
+    fn tuples() {
+        let parsed = <(String, usize)>::from_record(record(["hello", "4"])).unwrap();
+        assert_eq!(parsed, ("hello".to_owned(), 4));
+    }
+
+    #[test]
+    fn long_tuples() {
+        type Row = (
+            u8,
+            u16,
+            u32,
+            i8,
+            i16,
+            i32,
+            i64,
+            f32,
+            f64,
+            char,
+            bool,
+            String,
+        );
+        let fields = [
+            "1", "2", "3", "-4", "-5", "-6", "-7", "8.5", "9.5", "j", "true", "l",
+        ];
+        let parsed = Row::from_record(record(fields)).unwrap();
+        assert_eq!(parsed.9, 'j');
+        assert_eq!(parsed.11, "l");
+
+        let mut fields = fields;
+        fields[10] = "yes";
+        let error = Row::from_record(record(fields)).unwrap_err();
+        assert_eq!(error.column(), 10);
+    }
+
+    #[test]
+    fn string_record() {
+        let record = csv::StringRecord::from(vec!["1", "2"]);
+        let parsed = <[u8; 2]>::from_string_record(&record).unwrap();
+        assert_eq!(parsed, [1, 2]);
+
+        let record = csv::StringRecord::from(vec!["1", "2", "3"]);
+        assert!(<[u8; 2]>::from_string_record(&record).is_err());
+    }
+
+    #[test]
+    fn error() {
+        let error = <[f64; 2]>::from_record(record(["3.14", "pi"])).unwrap_err();
+        assert_eq!(error.column(), 1);
+        assert_eq!(error.text(), "pi");
+    }
+}
@@ -0,0 +1,329 @@
+/*!
+Writing datasets as JSON and JSON Lines, using the [`JsonLayout`] enum
+*/
+
+use std::io::{self, Write};
+
+use crate::field::{Literal, RecordBuf};
+use crate::format::FloatFormats;
+use crate::options::{NonFinite, SaveOptions};
+use crate::Datapoint;
+use crate::Error;
+
+/**
+The layout of a dataset written as JSON.
+
+Missing values (including non-finite floats) are written as `null`, and bools as `true` or `false`.
+Other fields are written as a JSON number if their text is a valid JSON number, and as a string otherwise,
+so an empty string is written as `""`.
+Without labels, objects are replaced by arrays.
+
+```
+use delfi::{Dataset, JsonLayout};
+
+let ds = Dataset::from_datapoints([(0, "a"), (1, "b")]).with_labels(["step", "name"]);
+
+let mut records = Vec::new();
+ds.write_json_to(&mut records, JsonLayout::Records).unwrap();
+assert_eq!(
+    String::from_utf8(records).unwrap(),
+    "[\n  {\"step\":0,\"name\":\"a\"},\n  {\"step\":1,\"name\":\"b\"}\n]\n"
+);
+
+let mut columns = Vec::new();
+ds.write_json_to(&mut columns, JsonLayout::Columns).unwrap();
+assert_eq!(
+    String::from_utf8(columns).unwrap(),
+    "{\n  \"step\":[0,1],\n  \"name\":[\"a\",\"b\"]\n}\n"
+);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonLayout {
+    /// An array with one object for each datapoint, keyed by the labels
+    #[default]
+    Records,
+    /// An object with one array for each column, keyed by the labels
+    Columns,
+}
+
+/// A writer of datapoints as JSON values, reusing a record buffer and a line buffer
+pub(crate) struct JsonWriter<'a, W: Write> {
+    writer: io::BufWriter<W>,
+    labels: Option<&'a [String]>,
+    record: RecordBuf,
+    line: String,
+}
+
+impl<'a, W: Write> JsonWriter<'a, W> {
+    /// Create a JSON writer, writing floats using the given formats
+    pub(crate) fn new(
+        writer: W,
+        labels: Option<&'a [String]>,
+        float_formats: &FloatFormats,
+    ) -> Self {
+        let mut record = RecordBuf::new();
+        // Non-finite floats are written as missing values, which become null
+        record.set_missing(&SaveOptions::new().with_non_finite(NonFinite::Replace));
+        record.float_formats_mut().clone_from(float_formats);
+        Self {
+            writer: io::BufWriter::new(writer),
+            labels,
+            record,
+            line: String::new(),
+        }
+    }
+
+    /// Write all datapoints using the given layout
+    pub(crate) fn write<const N: usize, D>(
+        &mut self,
+        data: &[D],
+        layout: JsonLayout,
+    ) -> Result<(), Error>
+    where
+        D: Datapoint<N>,
+    {
+        match layout {
+            JsonLayout::Records => self.write_records(data)?,
+            JsonLayout::Columns => self.write_columns(data)?,
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Write all datapoints as JSON Lines, with one value per line
+    pub(crate) fn write_lines<const N: usize, D>(&mut self, data: &[D]) -> Result<(), Error>
+    where
+        D: Datapoint<N>,
+    {
+        for (row, datapoint) in data.iter().enumerate() {
+            self.line.clear();
+            self.push_datapoint(row, datapoint)?;
+            self.line.push('\n');
+            self.writer.write_all(self.line.as_bytes())?;
+        }
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Write an array with one value for each datapoint
+    fn write_records<const N: usize, D: Datapoint<N>>(&mut self, data: &[D]) -> Result<(), Error> {
+        self.writer.write_all(b"[")?;
+        for (row, datapoint) in data.iter().enumerate() {
+            self.line.clear();
+            self.line.push_str(if row == 0 { "\n  " } else { ",\n  " });
+            self.push_datapoint(row, datapoint)?;
+            self.writer.write_all(self.line.as_bytes())?;
+        }
+        self.writer
+            .write_all(if data.is_empty() { b"]\n" } else { b"\n]\n" })?;
+        Ok(())
+    }
+
+    /// Write an object (or array, without labels) with one array for each column
+    fn write_columns<const N: usize, D: Datapoint<N>>(&mut self, data: &[D]) -> Result<(), Error> {
+        let mut columns: Vec<String> = (0..N).map(|_| String::from("[")).collect();
+        for (row, datapoint) in data.iter().enumerate() {
+            self.write_record(row, datapoint)?;
+            for (column, values) in columns.iter_mut().enumerate() {
+                if row > 0 {
+                    values.push(',');
+                }
+                push_value(values, &self.record, column);
+            }
+        }
+
+        let (open, close) = if self.labels.is_some() {
+            ("{", "}")
+        } else {
+            ("[", "]")
+        };
+        self.writer.write_all(open.as_bytes())?;
+        for (column, values) in columns.iter().enumerate() {
+            self.line.clear();
+            self.line
+                .push_str(if column == 0 { "\n  " } else { ",\n  " });
+            if let Some(label) = self.labels.and_then(|labels| labels.get(column)) {
+                push_string(&mut self.line, label);
+                self.line.push(':');
+            }
+            self.writer.write_all(self.line.as_bytes())?;
+            self.writer.write_all(values.as_bytes())?;
+            self.writer.write_all(b"]")?;
+        }
+        writeln!(self.writer, "\n{close}")?;
+        Ok(())
+    }
+
+    /// Add a datapoint to the line buffer, as an object (or array, without labels)
+    fn push_datapoint<const N: usize, D: Datapoint<N>>(
+        &mut self,
+        row: usize,
+        datapoint: &D,
+    ) -> Result<(), Error> {
+        self.write_record(row, datapoint)?;
+        let (open, close) = if self.labels.is_some() {
+            ('{', '}')
+        } else {
+            ('[', ']')
+        };
+        self.line.push(open);
+        for column in 0..self.record.len() {
+            if column > 0 {
+                self.line.push(',');
+            }
+            if let Some(labels) = self.labels {
+                let label = labels.get(column).ok_or(Error::Shape {
+                    path: None,
+                    row,
+                    expected: labels.len(),
+                    found: self.record.len(),
+                })?;
+                push_string(&mut self.line, label);
+                self.line.push(':');
+            }
+            push_value(&mut self.line, &self.record, column);
+        }
+        self.line.push(close);
+        Ok(())
+    }
+
+    /// Write the fields of a datapoint to the record buffer, failing unless there are exactly `N` of them
+    fn write_record<const N: usize, D: Datapoint<N>>(
+        &mut self,
+        row: usize,
+        datapoint: &D,
+    ) -> Result<(), Error> {
+        self.record.clear();
+        datapoint.write_record(&mut self.record);
+        if self.record.len() != N {
+            return Err(Error::Shape {
+                path: None,
+                row,
+                expected: N,
+                found: self.record.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/**
+Add the field at the given column as a JSON value:
+Null if missing, a boolean if a bool, a number if the text is one, and otherwise a string
+*/
+fn push_value(json: &mut String, record: &RecordBuf, column: usize) {
+    let text = record.get(column).unwrap_or_default();
+    match record.literal(column) {
+        Some(Literal::Missing) => json.push_str("null"),
+        Some(Literal::Bool) => json.push_str(text),
+        None => push_text(json, text),
+    }
+}
+
+/// Add text as a JSON number if it is one, and otherwise as a string
+fn push_text(json: &mut String, text: &str) {
+    if is_number(text) {
+        json.push_str(text);
+    } else {
+        push_string(json, text);
+    }
+}
+
+/// Add text as a JSON string, escaping quotes, backslashes and control characters
+fn push_string(json: &mut String, text: &str) {
+    json.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if ch < ' ' => {
+                use std::fmt::Write as _;
+                let _ = write!(json, "\\u{:04x}", u32::from(ch));
+            }
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+}
+
+/// Check whether text is a JSON number, such as `-12`, `0.5` or `1e-7` (but not `+1`, `.5` or `NaN`)
+fn is_number(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        *i - start
+    };
+
+    // The integer part has no leading zeros
+    let start = i;
+    let integer = digits(&mut i);
+    if integer == 0 || (integer > 1 && bytes[start] == b'0') {
+        return false;
+    }
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if digits(&mut i) == 0 {
+            return false;
+        }
+    }
+    i == bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        for text in ["0", "-0", "12", "-1.5", "0.25", "1e-7", "2.5E+10", "1e5"] {
+            assert!(is_number(text), "{text}");
+        }
+        for text in [
+            "", "-", "+1", ".5", "1.", "01", "1e", "1e+", "NaN", "inf", "0x10", "1 ",
+        ] {
+            assert!(!is_number(text), "{text}");
+        }
+    }
+
+    #[test]
+    fn values() {
+        let mut record = RecordBuf::new();
+        record.set_missing(&SaveOptions::new().with_non_finite(NonFinite::Replace));
+        (
+            None::<u8>,
+            f64::NAN,
+            true,
+            "",
+            1.5,
+            "true",
+            "a \"b\"\\\n\u{1}",
+        )
+            .write_record(&mut record);
+        let mut json = String::new();
+        for column in 0..record.len() {
+            push_value(&mut json, &record, column);
+            json.push(' ');
+        }
+        assert_eq!(
+            json,
+            "null null true \"\" 1.5 \"true\" \"a \\\"b\\\"\\\\\\n\\u0001\" "
+        );
+    }
+}
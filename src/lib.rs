@@ -46,6 +46,51 @@ pub mod dataset;
 /// Module containing code related to the Datapoint trait
 pub mod datapoint;
 
+/// Module containing code related to the FromRecord trait
+pub mod from_record;
+
+/// Module containing code related to the Columns trait
+pub mod columns;
+
+/// Module containing the DynDataset struct and code related to the DynDatapoint trait
+pub mod dyn_dataset;
+
+/// Module containing the Field trait and the RecordBuf used for writing records
+pub mod field;
+
+/// Module containing the Nested trait used for flattening derived datapoints
+pub mod nested;
+
+/// Module containing the FloatFormat used for writing floats
+pub mod format;
+
+/// Module containing the error type of this crate
+pub mod error;
+
+/// Module containing the streaming DatasetWriter
+pub mod writer;
+
+/// Module containing options for configuring the csv output
+pub mod options;
+
+/// Module containing the Compression used for compressed files
+pub mod compression;
+
+/// Module containing the JsonLayout used for writing datasets as JSON
+pub mod json;
+
+mod output;
+
+pub use compression::Compression;
+pub use dyn_dataset::{DynDatapoint, DynDataset};
+pub use error::Error;
+pub use field::{Field, RecordBuf};
+pub use format::FloatFormat;
+pub use json::JsonLayout;
+pub use nested::Nested;
+pub use options::{Header, NonFinite, QuoteStyle, SaveOptions, Terminator};
+pub use writer::{DatasetWriter, FlushPolicy};
+
 /**
 A dataset is a collection of datapoints (for more information on this see the [Datapoint] trait).
 
@@ -99,6 +144,7 @@ let ds = dataset!{
 #[derive(Debug, Clone)]
 pub struct Dataset<const COLS: usize, Data: Datapoint<COLS>> {
     labels: Option<[String; COLS]>,
+    float_formats: format::FloatFormats,
     data: Vec<Data>,
 }
 
@@ -106,11 +152,98 @@ pub struct Dataset<const COLS: usize, Data: Datapoint<COLS>> {
 A datapoint is a collection of dataelements which can be recorded to the csv-format.
 */
 pub trait Datapoint<const N: usize> {
+    /**
+    Record the datapoint as a number of fields
+    */
     fn record(&self) -> [String; N];
+
+    /**
+    Write the fields of the datapoint into a reusable buffer.
+
+    This is used when saving and writing datasets. The default implementation uses [`record`](Datapoint::record),
+    whereas arrays, tuples and derived datapoints write their elements directly using the [`Field`]
+    trait, without allocating a string for every field.
+    */
+    fn write_record(&self, record: &mut RecordBuf) {
+        for field in self.record() {
+            record.push_str(&field);
+        }
+    }
 }
 
 /**
-Derive Datapoint trait for a given struct (named or unnamed). Unit structs and enums are not supported.
+A datapoint which knows the labels of its elements, such as a struct deriving [Datapoint]
+(where the labels are the field names, or `0`, `1`, ... for tuple structs).
+
+```
+use delfi::{Datapoint, Dataset, Labelled};
+
+struct Sample {
+    time: f64,
+    value: f64,
+}
+
+impl Datapoint<2> for Sample {
+    fn record(&self) -> [String; 2] {
+        [self.time.to_string(), self.value.to_string()]
+    }
+}
+
+impl Labelled<2> for Sample {
+    const LABELS: [&'static str; 2] = ["time", "value"];
+}
+
+let dataset = Dataset::from_labelled_datapoints([Sample { time: 0.5, value: 2.0 }]);
+assert_eq!(dataset.to_csv_string().unwrap(), "time,value\n0.5,2\n");
+```
+*/
+pub trait Labelled<const N: usize>: Datapoint<N> {
+    /// The label of each element of the datapoint
+    const LABELS: [&'static str; N];
+}
+
+/**
+The inverse of a [Datapoint]: A type which can be parsed from a record read from the csv-format.
+
+```
+use delfi::FromRecord;
+
+let record = [String::from("0.5"), String::from("2")];
+let datapoint = <(f64, usize)>::from_record(record).unwrap();
+assert_eq!(datapoint, (0.5, 2));
+```
+*/
+pub trait FromRecord<const N: usize>: Sized {
+    /**
+    Parse a record into a datapoint
+
+    # Errors
+    Returns an error if any of the fields in the record failed to parse
+    */
+    fn from_record(record: [String; N]) -> Result<Self, from_record::ParseError>;
+
+    /**
+    Parse a record, as read by the csv crate, into a datapoint
+
+    # Errors
+    Returns an error if the record does not have exactly `N` fields,
+    or if any of the fields failed to parse
+    */
+    fn from_string_record(record: &csv::StringRecord) -> Result<Self, from_record::ParseError> {
+        let fields: Vec<String> = record.iter().map(ToOwned::to_owned).collect();
+        let record: [String; N] = fields
+            .try_into()
+            .map_err(|fields: Vec<String>| from_record::ParseError::length(N, fields.len()))?;
+        Self::from_record(record)
+    }
+}
+
+/**
+Derive Datapoint trait for a given struct (named or unnamed) or enum, along with the [`Labelled`] trait
+using the names of the fields as labels. Unit structs are not supported.
+Fields are written using their [`Field`] implementation, or their [`Display`](std::fmt::Display) implementation
+for types which only implement `Display`. Generic structs are supported, where fields of generic types
+are required to implement [`Field`].
 
 ```
 use delfi::Datapoint;
@@ -121,28 +254,191 @@ struct Count {
     i: usize,
 }
 ```
+
+Fields can be configured using the `delfi` attribute:
+- `#[delfi(rename = "...")]` sets the label of the column, which is otherwise the name (or index) of the field
+- `#[delfi(skip)]` leaves the field out of the record, so it does not count towards the number of columns
+- `#[delfi(order = N)]` puts the field in column `N`, with the other fields filling the remaining columns in order
+- `#[delfi(flatten)]` expands a field into one column per element, with labels prefixed by the label of the field
+  (such as `pos.x`). This works for fields which themselves derive `Datapoint` (see [`Nested`]), and for arrays
+  with a literal length (labelled `pos.0`, `pos.1`, ...). Fields of generic types can not be flattened,
+  since their number of columns must be known without the generic parameters.
+- `#[delfi(format = "...")]` writes the field using a format string with a single argument, such as `"{:.6e}"`
+- `#[delfi(with = "...")]` writes the field using a function taking a reference to it, such as `fn(&f64) -> String`,
+  where the returned value may be anything implementing [`Field`]
+
+Formatting only affects how a field is written, so a `FromRecord` implementation still parses it as usual.
+
+```
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(rename = "value [m]")]
+    value: f64,
+    #[delfi(order = 0, rename = "time [s]")]
+    time: f64,
+    #[delfi(skip)]
+    cache: Vec<f64>,
+}
+
+let sample = Sample { value: 2.5, time: 0.5, cache: Vec::new() };
+assert_eq!(sample.record(), ["0.5", "2.5"]);
+```
+
+```
+use delfi::{Datapoint, Labelled};
+
+#[derive(Datapoint)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Datapoint)]
+struct Particle {
+    #[delfi(flatten)]
+    pos: Vec3,
+    #[delfi(flatten, rename = "v")]
+    velocity: [f64; 2],
+}
+
+assert_eq!(Particle::LABELS, ["pos.x", "pos.y", "pos.z", "v.0", "v.1"]);
+```
+
+```
+use delfi::Datapoint;
+
+fn degrees(radians: &f64) -> f64 {
+    radians.to_degrees()
+}
+
+#[derive(Datapoint)]
+struct Measurement {
+    #[delfi(format = "{:.3e}")]
+    time: f64,
+    #[delfi(with = "degrees")]
+    angle: f64,
+}
+
+let measurement = Measurement { time: 1500.0, angle: std::f64::consts::PI };
+assert_eq!(measurement.record(), ["1.500e3", "180"]);
+```
+
+Enums are recorded with a column holding the name of the variant (labelled with the name of the enum),
+followed by the fields of all variants, where fields with the same label share a column.
+Fields which are absent from a variant are written as missing values (see [`SaveOptions::with_missing`]).
+Variants can be renamed using the `delfi` attribute.
+
+```
+use delfi::{Datapoint, Labelled};
+
+#[derive(Datapoint)]
+enum Event {
+    Collision { pos: f64, energy: f64 },
+    #[delfi(rename = "spawn")]
+    Spawn { pos: f64 },
+    Tick,
+}
+
+assert_eq!(Event::LABELS, ["Event", "pos", "energy"]);
+assert_eq!(Event::Spawn { pos: 1.5 }.record(), ["spawn", "1.5", ""]);
+assert_eq!(Event::Tick.record(), ["Tick", "", ""]);
+```
 */
 #[cfg(feature = "macros")]
 pub use delfi_macros::Datapoint;
 
 /**
-Macro for creating a dataset from a set of labelled columns
+Derive [`FromRecord`] trait for a given struct (named or unnamed), parsing each field using [`FromStr`](std::str::FromStr).
+Unit structs and enums are not supported. Fields of generic types are required to implement `FromStr`
+(with an error implementing `Display`). The `delfi` attribute is used the same way as for
+the [`Datapoint`](derive@Datapoint) derive, where skipped fields are set to their default value.
+
+```
+use delfi::{Datapoint, FromRecord};
+
+#[derive(Datapoint, FromRecord)]
+struct Count {
+    ch: char,
+    i: usize,
+}
+
+let record = [String::from("a"), String::from("32")];
+let count = Count::from_record(record).unwrap();
+assert_eq!(count.i, 32);
+```
+*/
+#[cfg(feature = "macros")]
+pub use delfi_macros::FromRecord;
+
+/**
+Macro for creating a dataset from a set of labelled columns.
+Each column can have its own type of element, and the datapoints of the dataset are tuples
+(see [`Dataset::from_mixed_columns`]).
 
 # Examples
 ```
 use delfi::dataset;
 
+let steps: Vec<usize> = vec![0, 1, 2];
+let energies: Vec<f64> = vec![1.5, 1.25, 1.125];
+let phases = ["solid", "liquid", "gas"];
+let ds = dataset!{
+    "step" => steps,
+    "energy" => energies,
+    "phase" => phases,
+};
+# ds.save("./resources/data/examples/mixed.csv").unwrap();
+```
+
+Since tuples are only datapoints up to a length of 16, this supports up to 16 columns.
+Starting with `array;` instead requires the columns to share a type of element,
+in which case the datapoints of the dataset are arrays (see [`Dataset::from_columns`]),
+and there is no limit on the number of columns:
+```
+use delfi::{dataset, Dataset};
+
 let t = [0, 1, 2, 3, 4, 5];
 let x = [2, 3, 5, 8, 12, 17];
-let ds = dataset!{
+let ds: Dataset<2, [i32; 2]> = dataset!{
+    array;
     "time" => t,
     "length" => x,
 };
 ```
+
+Starting with `strict;` requires the columns to be of equal length (see [`Dataset::try_from_mixed_columns`]),
+returning a `Result` instead of truncating the columns to the shortest one.
+The two can be combined as `array; strict;`:
+```
+use delfi::dataset;
+
+let t = vec![0, 1, 2, 3, 4, 5];
+let x = vec![2, 3, 5, 8, 12];
+let result = dataset!{
+    strict;
+    "time" => t,
+    "length" => x,
+};
+assert!(result.is_err());
+```
 */
 #[macro_export]
 macro_rules! dataset {
+    (array; strict; $($name:expr => $value:expr), + $(,)?) => {{
+        $crate::Dataset::try_from_columns([$($value),+])
+            .map(|dataset| dataset.with_labels([$($name),+]))
+    }};
+    (array; $($name:expr => $value:expr), + $(,)?) => {{
+        $crate::Dataset::from_columns([$($value),+]).with_labels([$($name),+])
+    }};
+    (strict; $($name:expr => $value:expr), + $(,)?) => {{
+        $crate::Dataset::try_from_mixed_columns(($($value,)+))
+            .map(|dataset| dataset.with_labels([$($name),+]))
+    }};
     ($($name:expr => $value:expr), + $(,)?) => {{
-        delfi::Dataset::from_columns([$($value),+]).with_labels([$($name),+])
+        $crate::Dataset::from_mixed_columns(($($value,)+)).with_labels([$($name),+])
     }};
 }
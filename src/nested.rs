@@ -0,0 +1,126 @@
+/*!
+Datapoints nested within derived datapoints, using the [`Nested`] trait
+*/
+
+use crate::RecordBuf;
+
+/**
+A datapoint which can be nested within a derived datapoint using `#[delfi(flatten)]`,
+expanding into one column for each of its elements. This is implemented by the `Datapoint` derive macro.
+
+The number of elements is an associated constant (unlike the generic parameter of [`Datapoint`](crate::Datapoint)),
+so the number of columns of the outer datapoint can be computed at compile time.
+*/
+pub trait Nested {
+    /// The number of elements of the datapoint
+    const WIDTH: usize;
+
+    /// The label of each element of the datapoint
+    const NESTED_LABELS: &'static [&'static str];
+
+    /**
+    Write the elements of the datapoint as fields at the end of the record
+    */
+    fn write_nested(&self, record: &mut RecordBuf);
+}
+
+/// The total length of the labels after prefixing each of them
+#[doc(hidden)]
+#[must_use]
+pub const fn prefixed_len(prefix: &str, labels: &[&str]) -> usize {
+    let mut len = 0;
+    let mut i = 0;
+    while i < labels.len() {
+        len += prefix.len() + labels[i].len();
+        i += 1;
+    }
+    len
+}
+
+/// The bytes of the labels after prefixing each of them, one after another
+#[doc(hidden)]
+#[must_use]
+pub const fn prefixed_bytes<const LEN: usize>(prefix: &str, labels: &[&str]) -> [u8; LEN] {
+    let mut bytes = [0; LEN];
+    let mut at = 0;
+    let mut i = 0;
+    while i < labels.len() {
+        at = copy(&mut bytes, at, prefix.as_bytes());
+        at = copy(&mut bytes, at, labels[i].as_bytes());
+        i += 1;
+    }
+    bytes
+}
+
+/// Copy the source into the bytes starting at the given position, returning the position after it
+const fn copy<const LEN: usize>(bytes: &mut [u8; LEN], mut at: usize, source: &[u8]) -> usize {
+    let mut i = 0;
+    while i < source.len() {
+        bytes[at] = source[i];
+        at += 1;
+        i += 1;
+    }
+    at
+}
+
+/**
+Split the bytes written by [`prefixed_bytes`] back into the prefixed labels
+
+# Panics
+Panics (at compile time) if the bytes were not written from the same prefix and labels
+*/
+#[doc(hidden)]
+#[must_use]
+pub const fn split_prefixed<const WIDTH: usize>(
+    bytes: &'static [u8],
+    prefix: &str,
+    labels: &[&str],
+) -> [&'static str; WIDTH] {
+    let mut split = [""; WIDTH];
+    let mut rest = bytes;
+    let mut i = 0;
+    while i < WIDTH {
+        let (label, tail) = rest.split_at(prefix.len() + labels[i].len());
+        split[i] = match std::str::from_utf8(label) {
+            Ok(label) => label,
+            Err(_) => panic!("prefixed labels are split where they were joined"),
+        };
+        rest = tail;
+        i += 1;
+    }
+    split
+}
+
+/// Join the labels of each field (or nested datapoint) into the labels of the outer datapoint
+#[doc(hidden)]
+#[must_use]
+pub const fn join<const N: usize>(segments: &[&[&'static str]]) -> [&'static str; N] {
+    let mut labels = [""; N];
+    let mut at = 0;
+    let mut i = 0;
+    while i < segments.len() {
+        let mut j = 0;
+        while j < segments[i].len() {
+            labels[at] = segments[i][j];
+            at += 1;
+            j += 1;
+        }
+        i += 1;
+    }
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INNER: [&str; 2] = ["x", "y"];
+    const BYTES: [u8; prefixed_len("pos.", &INNER)] = prefixed_bytes("pos.", &INNER);
+    const PREFIXED: [&str; 2] = split_prefixed(&BYTES, "pos.", &INNER);
+    const LABELS: [&str; 4] = join(&[&["t"], &PREFIXED, &["mass"]]);
+
+    #[test]
+    fn labels() {
+        assert_eq!(LABELS, ["t", "pos.x", "pos.y", "mass"]);
+    }
+}
@@ -0,0 +1,417 @@
+/*!
+Configuration of the csv dialect and the writing of missing values using the [`SaveOptions`] struct
+*/
+
+use std::io;
+use std::path::Path;
+
+pub use csv::{QuoteStyle, Terminator};
+
+use crate::Compression;
+
+/**
+Options for how a dataset is written to csv.
+The default options are the same as those used by [`Dataset::save`](crate::Dataset::save).
+The same options can be used to load the dataset back, using [`Dataset::load_with`](crate::Dataset::load_with).
+
+```
+use delfi::{Dataset, SaveOptions, Terminator};
+
+let t = [0.0, 0.5, 1.0];
+let x = [1.0, 2.0, 4.0];
+let ds = Dataset::from_columns([t, x]).with_labels(["time", "length"]);
+
+// Semicolon separated, with windows line endings
+let options = SaveOptions::new()
+    .with_delimiter(b';')
+    .with_terminator(Terminator::CRLF);
+ds.save_with("./resources/data/examples/options.csv", &options).unwrap();
+```
+*/
+#[derive(Debug, Clone)]
+pub struct SaveOptions {
+    delimiter: u8,
+    quote_style: QuoteStyle,
+    quote: u8,
+    escape: Option<u8>,
+    terminator: Terminator,
+    header: Header,
+    missing: String,
+    non_finite: NonFinite,
+    atomic: bool,
+    compression: Option<Compression>,
+    compression_level: Option<u32>,
+}
+
+impl SaveOptions {
+    /**
+    Create new options, equivalent to default
+    */
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            delimiter: b',',
+            quote_style: QuoteStyle::Necessary,
+            quote: b'"',
+            escape: None,
+            terminator: Terminator::Any(b'\n'),
+            header: Header::Auto,
+            missing: String::new(),
+            non_finite: NonFinite::Keep,
+            atomic: false,
+            compression: None,
+            compression_level: None,
+        }
+    }
+
+    /**
+    Take options, set the field delimiter (default `,`), and return options
+    */
+    #[must_use]
+    pub fn with_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /**
+    Take options, set when fields are quoted (default [`QuoteStyle::Necessary`]), and return options
+    */
+    #[must_use]
+    pub fn with_quote_style(mut self, quote_style: QuoteStyle) -> Self {
+        self.quote_style = quote_style;
+        self
+    }
+
+    /**
+    Take options, set the quote character (default `"`), and return options
+    */
+    #[must_use]
+    pub fn with_quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /**
+    Take options, set the escape character for quotes within quoted fields, and return options.
+    By default (`None`) quotes are escaped by doubling them.
+    */
+    #[must_use]
+    pub fn with_escape(mut self, escape: Option<u8>) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /**
+    Take options, set the line terminator (default `\n`), and return options
+    */
+    #[must_use]
+    pub fn with_terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /**
+    Take options, set whether labels are written as (and loaded from) a header row
+    (default [`Header::Auto`]), and return options.
+    Passing `true` or `false` is the same as [`Header::Present`] or [`Header::Absent`].
+    */
+    #[must_use]
+    pub fn with_header(mut self, header: impl Into<Header>) -> Self {
+        self.header = header.into();
+        self
+    }
+
+    /**
+    Take options, set the text written for missing values such as `None` (default empty), and return options.
+    Common choices are `NA`, `NaN` and `null`.
+
+    ```
+    use delfi::{Dataset, SaveOptions};
+
+    let ds = Dataset::from_datapoints([[Some(1.0), None], [None, Some(0.5)]]);
+    let options = SaveOptions::new().with_missing("NA");
+    let mut output = Vec::new();
+    ds.write_to_with(&mut output, &options).unwrap();
+    assert_eq!(output, b"1,NA\nNA,0.5\n");
+    ```
+    */
+    #[must_use]
+    pub fn with_missing(mut self, missing: impl Into<String>) -> Self {
+        self.missing = missing.into();
+        self
+    }
+
+    /**
+    Take options, set how non-finite floats are written (default [`NonFinite::Keep`]), and return options
+    */
+    #[must_use]
+    pub fn with_non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /**
+    Whether labels are written as a header row
+    */
+    #[must_use]
+    pub fn header(&self) -> bool {
+        self.header != Header::Absent
+    }
+
+    /**
+    Whether the first row is a header row when loading, see [`Header`]
+    */
+    #[must_use]
+    pub fn header_mode(&self) -> Header {
+        self.header
+    }
+
+    /**
+    Take options, set whether files are saved atomically (default `false`), and return options.
+
+    When saving atomically the data is written to a temporary file in the same directory,
+    which is synced to disk and then renamed to the given path. Readers of the file will therefore
+    only ever see the complete file, even if writing is interrupted. A file being replaced keeps its permissions,
+    and on unix the directory is synced after the rename. For appending,
+    the existing file is copied to the temporary file before appending to it.
+    For a [`DatasetWriter`](crate::DatasetWriter) the file is renamed when the writer is finished,
+    and the temporary file is removed if it is dropped without being finished.
+
+    ```
+    use delfi::{Dataset, SaveOptions};
+
+    let ds = Dataset::from_datapoints([[1, 2], [3, 4]]).with_labels(["a", "b"]);
+    let options = SaveOptions::new().with_atomic(true);
+    ds.save_with("./resources/data/examples/atomic.csv", &options).unwrap();
+    ```
+    */
+    #[must_use]
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /**
+    Take options, set the compression of saved files, and return options.
+    By default the compression is chosen from the file extension, see [`Compression`].
+
+    ```
+    use delfi::{Compression, Dataset, SaveOptions};
+
+    // Plain csv, even though the extension says otherwise
+    let path = std::env::temp_dir().join("delfi-plain.csv.gz");
+    let ds = Dataset::from_datapoints([[1, 2], [3, 4]]);
+    let options = SaveOptions::new().with_compression(Compression::None);
+    ds.save_with(&path, &options).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "1,2\n3,4\n");
+    # std::fs::remove_file(&path).unwrap();
+    ```
+    */
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /**
+    Take options, set the compression level, and return options.
+    The level is clamped to the levels of the codec: 0 to 9 for gzip and xz, and 1 to 22 for zstd.
+    By default the default level of the codec is used (6 for gzip and xz, and 3 for zstd).
+    */
+    #[must_use]
+    pub fn with_compression_level(mut self, level: u32) -> Self {
+        self.compression_level = Some(level);
+        self
+    }
+
+    /**
+    The compression of saved files, if set
+    */
+    #[must_use]
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    /**
+    The compression level, if set
+    */
+    #[must_use]
+    pub fn compression_level(&self) -> Option<u32> {
+        self.compression_level
+    }
+
+    /**
+    The compression of the file at the given path, which is chosen from its extension unless set.
+    Fails if the extension belongs to a codec which is not enabled.
+    */
+    pub(crate) fn compression_for(&self, filepath: &Path) -> io::Result<Compression> {
+        self.compression
+            .map_or_else(|| Compression::for_path(filepath), Ok)
+    }
+
+    /**
+    Whether files are saved atomically
+    */
+    #[must_use]
+    pub fn atomic(&self) -> bool {
+        self.atomic
+    }
+
+    /**
+    The text written for missing values
+    */
+    #[must_use]
+    pub fn missing(&self) -> &str {
+        &self.missing
+    }
+
+    /**
+    How non-finite floats are written
+    */
+    #[must_use]
+    pub fn non_finite(&self) -> NonFinite {
+        self.non_finite
+    }
+
+    /// Create a csv reader for the same dialect, used to read the first row of files being appended to
+    pub(crate) fn reader<R: std::io::Read>(&self, reader: R) -> csv::Reader<R> {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.escape.is_none())
+            .terminator(self.terminator);
+        builder.from_reader(reader)
+    }
+
+    /// Create a csv writer configured with the given options
+    pub(crate) fn writer<W: std::io::Write>(&self, writer: W) -> csv::Writer<W> {
+        let mut builder = csv::WriterBuilder::new();
+        builder
+            .delimiter(self.delimiter)
+            .quote_style(self.quote_style)
+            .quote(self.quote)
+            .terminator(self.terminator);
+        match self.escape {
+            Some(escape) => builder.double_quote(false).escape(escape),
+            None => builder.double_quote(true),
+        };
+        builder.from_writer(writer)
+    }
+}
+
+/**
+Default is equivalent to new
+*/
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+Whether files have a header row of labels, see [`SaveOptions::with_header`].
+
+Labels are written as a header row unless it is [`Header::Absent`].
+When loading, [`Header::Auto`] detects the header row:
+The first row is used as labels only if it can not be parsed as a datapoint.
+Datapoints which can parse any text (such as strings) therefore need [`Header::Present`] to load their labels.
+
+```
+use delfi::{Dataset, Header, SaveOptions};
+
+let labelled: Dataset<2, [u8; 2]> = Dataset::read_from("a,b\n1,2\n".as_bytes()).unwrap();
+assert_eq!(labelled.get_labels().unwrap(), &["a", "b"]);
+
+let unlabelled: Dataset<2, [u8; 2]> = Dataset::read_from("1,2\n3,4\n".as_bytes()).unwrap();
+assert_eq!(unlabelled.get_labels(), None);
+assert_eq!(unlabelled.n_datapoints(), 2);
+
+let options = SaveOptions::new().with_header(Header::Present);
+let text: Dataset<2, [String; 2]> = Dataset::read_from_with("a,b\nc,d\n".as_bytes(), &options).unwrap();
+assert_eq!(text.get_labels().unwrap(), &["a", "b"]);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Header {
+    /// Write labels as a header row, and load the first row as labels if it is not a datapoint
+    #[default]
+    Auto,
+    /// Write labels as a header row, and always load the first row as labels
+    Present,
+    /// Neither write nor load labels, as every row is a datapoint
+    Absent,
+}
+
+impl From<bool> for Header {
+    fn from(header: bool) -> Self {
+        if header {
+            Self::Present
+        } else {
+            Self::Absent
+        }
+    }
+}
+
+/**
+How non-finite floats (`NaN`, `inf` and `-inf`) are written
+
+```
+use delfi::{Dataset, Error, NonFinite, SaveOptions};
+
+let ds = Dataset::from_datapoints([[1.0, f64::NAN]]);
+
+let options = SaveOptions::new().with_missing("NA").with_non_finite(NonFinite::Replace);
+let mut output = Vec::new();
+ds.write_to_with(&mut output, &options).unwrap();
+assert_eq!(output, b"1,NA\n");
+
+let options = SaveOptions::new().with_non_finite(NonFinite::Reject);
+let result = ds.write_to_with(Vec::new(), &options);
+assert!(matches!(result, Err(Error::NonFinite { row: 0, column: 1, .. })));
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// Write the value as is, as `NaN`, `inf` or `-inf`
+    #[default]
+    Keep,
+    /// Write the value as missing, see [`SaveOptions::with_missing`]
+    Replace,
+    /// Return an [`Error::NonFinite`](crate::Error::NonFinite) instead of writing the row
+    Reject,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(options: &SaveOptions, records: &[[&str; 2]]) -> String {
+        let mut writer = options.writer(Vec::new());
+        for record in records {
+            writer.write_record(record).unwrap();
+        }
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn default() {
+        let output = write(&SaveOptions::default(), &[["a", "b,c"]]);
+        assert_eq!(output, "a,\"b,c\"\n");
+    }
+
+    #[test]
+    fn dialect() {
+        let options = SaveOptions::new()
+            .with_delimiter(b'\t')
+            .with_quote_style(QuoteStyle::Always)
+            .with_quote(b'\'')
+            .with_escape(Some(b'\\'))
+            .with_terminator(Terminator::CRLF);
+        let output = write(&options, &[["a", "it's"]]);
+        assert_eq!(output, "'a'\t'it\\'s'\r\n");
+    }
+}
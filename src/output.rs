@@ -0,0 +1,350 @@
+/*!
+Shared code for writing datasets to files and writers
+*/
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::compression::{self, Encoder};
+use crate::field::RecordBuf;
+use crate::format::FloatFormats;
+use crate::Compression;
+use crate::Datapoint;
+use crate::Error;
+use crate::SaveOptions;
+
+/**
+A csv writer which keeps track of the number of rows written,
+and reuses a single record buffer for writing datapoints
+*/
+#[derive(Debug)]
+pub(crate) struct CsvWriter<W: io::Write> {
+    writer: csv::Writer<W>,
+    record: RecordBuf,
+    header: bool,
+    n_rows: usize,
+}
+
+impl<W: io::Write> CsvWriter<W> {
+    /// Create a csv writer using the given options
+    pub(crate) fn new(writer: W, options: &SaveOptions) -> Self {
+        let mut record = RecordBuf::new();
+        record.set_missing(options);
+        Self {
+            writer: options.writer(writer),
+            record,
+            header: options.header(),
+            n_rows: 0,
+        }
+    }
+
+    /// The formats used for floats in each column
+    pub(crate) fn float_formats_mut(&mut self) -> &mut FloatFormats {
+        self.record.float_formats_mut()
+    }
+
+    /// Whether labels are written as a header row
+    pub(crate) fn header(&self) -> bool {
+        self.header
+    }
+
+    /// Write the labels as a header row, unless disabled by the options
+    pub(crate) fn write_labels(&mut self, labels: &[String]) -> Result<(), Error> {
+        if self.header {
+            self.write_record(labels)?;
+        }
+        Ok(())
+    }
+
+    /// Write a datapoint using its buffer-based recording
+    pub(crate) fn write_datapoint<const N: usize, D>(&mut self, datapoint: &D) -> Result<(), Error>
+    where
+        D: Datapoint<N> + ?Sized,
+    {
+        self.write_fields(|record| datapoint.write_record(record))
+    }
+
+    /// Write a record of the fields written into the record buffer by the given function
+    pub(crate) fn write_fields<F>(&mut self, write: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut RecordBuf),
+    {
+        self.record.clear();
+        write(&mut self.record);
+        if let Some(column) = self.record.rejected() {
+            return Err(Error::NonFinite {
+                path: None,
+                row: self.n_rows,
+                column,
+                value: self.record.get(column).unwrap_or_default().to_owned(),
+            });
+        }
+        self.writer
+            .write_byte_record(self.record.as_byte_record())
+            .map_err(Error::csv_at_row(self.n_rows))?;
+        self.n_rows += 1;
+        Ok(())
+    }
+
+    /// Write a record of fields
+    pub(crate) fn write_record<Record>(&mut self, record: Record) -> Result<(), Error>
+    where
+        Record: IntoIterator,
+        Record::Item: AsRef<[u8]>,
+    {
+        self.writer
+            .write_record(record)
+            .map_err(Error::csv_at_row(self.n_rows))?;
+        self.n_rows += 1;
+        Ok(())
+    }
+
+    /// Flush everything written to the underlying writer
+    pub(crate) fn flush(&mut self) -> Result<(), Error> {
+        self.writer.flush().map_err(Error::from)
+    }
+
+    /// Get a reference to the underlying writer
+    #[cfg(test)]
+    pub(crate) fn get_ref(&self) -> &W {
+        self.writer.get_ref()
+    }
+
+    /// Flush everything written and return the underlying writer
+    pub(crate) fn into_inner(self) -> Result<W, Error> {
+        // The error is copied, since csv 1.1 can only lend out the error of the flush
+        self.writer.into_inner().map_err(|e| {
+            let error = e.error();
+            Error::from(io::Error::new(error.kind(), error.to_string()))
+        })
+    }
+}
+
+/// Create the file at the given path and write to it, adding the path to any error
+pub(crate) fn save<F>(filepath: &Path, options: &SaveOptions, write: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut Encoder<File>) -> Result<(), Error>,
+{
+    let (file, temp) = create(filepath, options)?;
+    encode(file, filepath, options, write)
+        .and_then(|()| temp.map_or(Ok(()), TempFile::commit))
+        .map_err(|e| e.at_path(filepath))
+}
+
+/// Write to the file using the compression of the given path (unless set by the options)
+pub(crate) fn encode<F>(
+    file: File,
+    filepath: &Path,
+    options: &SaveOptions,
+    write: F,
+) -> Result<(), Error>
+where
+    F: FnOnce(&mut Encoder<File>) -> Result<(), Error>,
+{
+    let compression = options.compression_for(filepath)?;
+    let mut encoder = Encoder::new(file, compression, options.compression_level())?;
+    write(&mut encoder)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/**
+Create the file at the given path, truncating any existing file.
+When saving atomically a temporary file is created instead, which replaces the file once committed.
+Nothing is created if the compression of the file is not enabled.
+*/
+pub(crate) fn create(
+    filepath: &Path,
+    options: &SaveOptions,
+) -> Result<(File, Option<TempFile>), Error> {
+    let create = || -> Result<_, Error> {
+        options.compression_for(filepath)?;
+        if options.atomic() {
+            let (temp, file) = TempFile::create(filepath)?;
+            Ok((file, Some(temp)))
+        } else {
+            Ok((File::create(filepath)?, None))
+        }
+    };
+    create().map_err(|e| e.at_path(filepath))
+}
+
+/**
+Check that the file at the given path is plain csv,
+as files written by a `DatasetWriter` can not be compressed
+*/
+pub(crate) fn require_plain(filepath: &Path, options: &SaveOptions) -> Result<(), Error> {
+    let compression = options
+        .compression_for(filepath)
+        .map_err(|e| Error::from(e).at_path(filepath))?;
+    if compression == Compression::None {
+        return Ok(());
+    }
+    let message = "a DatasetWriter can not create compressed files, \
+                   use DatasetWriter::from_writer with an encoder instead";
+    let source = io::Error::new(io::ErrorKind::Unsupported, message);
+    Err(Error::from(source).at_path(filepath))
+}
+
+/// A file opened for appending, see [`open_append`]
+pub(crate) struct Appending {
+    pub file: File,
+    /// The first row of the file, if it has any rows
+    pub first: Option<Vec<String>>,
+    /// The temporary copy of the file which is appended to when saving atomically
+    pub temp: Option<TempFile>,
+}
+
+/**
+Open the file at the given path for appending, creating it if it does not exist,
+and read the first row of the file if it has any.
+When saving atomically the file is copied to a temporary file, which is appended to instead.
+*/
+pub(crate) fn open_append(filepath: &Path, options: &SaveOptions) -> Result<Appending, Error> {
+    let open = || -> Result<_, Error> {
+        let compression = options.compression_for(filepath)?;
+        let mut open_options = OpenOptions::new();
+        open_options.read(true).append(true);
+        let (file, temp) = if options.atomic() {
+            let (temp, _) = TempFile::create(filepath)?;
+            if filepath.exists() {
+                fs::copy(filepath, &temp.path)?;
+            }
+            (open_options.open(&temp.path)?, Some(temp))
+        } else {
+            (open_options.create(true).open(filepath)?, None)
+        };
+        let first = read_first(&file, compression, options)?;
+        Ok(Appending { file, first, temp })
+    };
+    open().map_err(|e| e.at_path(filepath))
+}
+
+/// Read the first row of a file, if it has any rows
+fn read_first(
+    file: &File,
+    compression: Compression,
+    options: &SaveOptions,
+) -> Result<Option<Vec<String>>, Error> {
+    // An empty file has no rows, and is not a valid compressed stream for every codec
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    let mut reader = options.reader(compression::decoder(file, compression)?);
+    let mut record = csv::StringRecord::new();
+    let first = reader
+        .read_record(&mut record)
+        .map_err(Error::csv_at_row(0))?
+        .then(|| record.iter().map(ToOwned::to_owned).collect());
+    Ok(first)
+}
+
+/**
+A temporary file in the same directory as the file it replaces,
+which is removed again unless it is committed
+*/
+#[derive(Debug)]
+pub(crate) struct TempFile {
+    path: PathBuf,
+    target: PathBuf,
+    committed: bool,
+}
+
+impl TempFile {
+    /// Create a new temporary file next to the target
+    fn create(target: &Path) -> io::Result<(Self, File)> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = target.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "path does not name a file")
+        })?;
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        let count = COUNT.fetch_add(1, Ordering::Relaxed);
+        temp_name.push(format!(".{}-{count}.tmp", process::id()));
+
+        let path = target.with_file_name(temp_name);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let temp = Self {
+            path,
+            target: target.to_owned(),
+            committed: false,
+        };
+        Ok((temp, file))
+    }
+
+    /**
+    Sync everything written to the temporary file to disk, and rename it to the target.
+    The permissions of an existing target are kept, and the rename itself is synced to disk (on unix).
+    */
+    pub(crate) fn commit(mut self) -> Result<(), Error> {
+        // The file is synced using a new handle, as the original may be owned by a writer
+        let file = OpenOptions::new().write(true).open(&self.path)?;
+        match fs::metadata(&self.target) {
+            Ok(metadata) => file.set_permissions(metadata.permissions())?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        file.sync_all()?;
+        fs::rename(&self.path, &self.target)?;
+        self.committed = true;
+        sync_parent(&self.target)?;
+        Ok(())
+    }
+}
+
+/// Sync the directory containing the given path, which makes a rename within it durable
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+/// Directories can not be opened and synced on other platforms
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/**
+Check that the first row of an existing file matches the labels being written,
+or only the number of columns if no labels are written
+*/
+pub(crate) fn check_existing(
+    first: &[String],
+    labels: Option<&[String]>,
+    cols: usize,
+) -> Result<(), Error> {
+    match labels {
+        Some(labels) if first != labels => Err(Error::Labels {
+            path: None,
+            expected: labels.to_vec(),
+            found: first.to_vec(),
+        }),
+        _ if first.len() != cols => Err(Error::Shape {
+            path: None,
+            row: 0,
+            expected: cols,
+            found: first.len(),
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,389 @@
+/*!
+Streaming output of datapoints using the [`DatasetWriter`] struct
+*/
+
+use std::fs::File;
+use std::io;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use crate::output::{self, CsvWriter, TempFile};
+use crate::Datapoint;
+use crate::Error;
+use crate::FloatFormat;
+use crate::Labelled;
+use crate::SaveOptions;
+
+/**
+Policy for when a [`DatasetWriter`] flushes its buffer to the underlying writer
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlushPolicy {
+    /// Only flush when the internal buffer is full, or when the writer is flushed or finished explicitly
+    #[default]
+    Buffered,
+    /// Flush after every datapoint
+    EveryDatapoint,
+    /// Flush after every `n` datapoints
+    Every(usize),
+}
+
+/**
+A writer which streams datapoints directly to csv, without keeping them in memory.
+
+The header row is written from the labels (if any) before the first datapoint.
+Use [`finish`](DatasetWriter::finish) when done writing, to make sure everything
+(including the header of an empty dataset) has been written.
+
+```
+use delfi::{DatasetWriter, FlushPolicy};
+
+let mut writer = DatasetWriter::create("./resources/data/examples/writer.csv")
+    .unwrap()
+    .with_labels(["step", "energy"])
+    .with_flush_policy(FlushPolicy::Every(100));
+
+for step in 0..1000 {
+    let energy = 0.5 * f64::from(step);
+    writer.write(&[f64::from(step), energy]).unwrap();
+}
+writer.finish().unwrap();
+```
+*/
+#[derive(Debug)]
+pub struct DatasetWriter<const COLS: usize, Data: Datapoint<COLS>, W: io::Write = File> {
+    writer: CsvWriter<W>,
+    path: Option<PathBuf>,
+    labels: Option<[String; COLS]>,
+    flush_policy: FlushPolicy,
+    started: bool,
+    /// The first row of the file being appended to, if it had any rows
+    existing: Option<Vec<String>>,
+    /// The temporary file being written to when saving atomically
+    temp: Option<TempFile>,
+    n_datapoints: usize,
+    data: PhantomData<fn(&Data)>,
+}
+
+impl<const COLS: usize, Data: Datapoint<COLS>> DatasetWriter<COLS, Data, File> {
+    /**
+    Creates a writer to the given file. The filepath must be valid, and any existing file is truncated.
+    Accepts anything path-like.
+
+    # Errors
+    Returns an error if the file could not be created
+    */
+    pub fn create<P: AsRef<Path>>(filepath: P) -> Result<Self, Error> {
+        Self::create_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Creates a writer to the given file, using the given options for the csv dialect.
+    The filepath must be valid, and any existing file is truncated.
+    Accepts anything path-like.
+
+    # Errors
+    Returns an error if the file could not be created,
+    or if it should be compressed (see [`Compression`](crate::Compression))
+    */
+    pub fn create_with<P: AsRef<Path>>(filepath: P, options: &SaveOptions) -> Result<Self, Error> {
+        let filepath = filepath.as_ref();
+        output::require_plain(filepath, options)?;
+        let (file, temp) = output::create(filepath, options)?;
+        let mut writer = Self::from_writer_with(file, options);
+        writer.path = Some(filepath.to_owned());
+        writer.temp = temp;
+        Ok(writer)
+    }
+
+    /**
+    Creates a writer which appends to the given file, creating it if it does not exist.
+    Accepts anything path-like.
+
+    If the file already has rows, its header row is checked against the labels before the first datapoint
+    (or only the number of columns, without labels), and the header row is not written again.
+
+    ```
+    use delfi::DatasetWriter;
+    # let _ = std::fs::remove_file("./resources/data/examples/writer-append.csv");
+
+    for night in 0..3 {
+        let mut writer = DatasetWriter::append("./resources/data/examples/writer-append.csv")
+            .unwrap()
+            .with_labels(["night", "result"]);
+        writer.write(&[night, 10 * night]).unwrap();
+        writer.finish().unwrap();
+    }
+    ```
+
+    # Errors
+    Returns an error if the file could not be opened or read
+    */
+    pub fn append<P: AsRef<Path>>(filepath: P) -> Result<Self, Error> {
+        Self::append_with(filepath, &SaveOptions::default())
+    }
+
+    /**
+    Creates a writer which appends to the given file, using the given options for the csv dialect.
+    See [`append`](DatasetWriter::append) for how the existing header row is checked.
+
+    # Errors
+    Returns an error if the file could not be opened or read,
+    or if it is compressed (see [`Compression`](crate::Compression))
+    */
+    pub fn append_with<P: AsRef<Path>>(filepath: P, options: &SaveOptions) -> Result<Self, Error> {
+        let filepath = filepath.as_ref();
+        output::require_plain(filepath, options)?;
+        let output::Appending {
+            file,
+            first: existing,
+            temp,
+        } = output::open_append(filepath, options)?;
+        let mut writer = Self::from_writer_with(file, options);
+        writer.path = Some(filepath.to_owned());
+        writer.temp = temp;
+        if existing.is_some() {
+            writer.existing = existing;
+            writer.started = false;
+        }
+        Ok(writer)
+    }
+}
+
+impl<const COLS: usize, Data: Datapoint<COLS>, W: io::Write> DatasetWriter<COLS, Data, W> {
+    /**
+    Creates a writer to anything implementing [`std::io::Write`]
+
+    ```
+    use delfi::DatasetWriter;
+
+    let mut writer = DatasetWriter::from_writer(Vec::new()).with_labels(["x", "y"]);
+    writer.write(&[1, 2]).unwrap();
+    let output = writer.finish().unwrap();
+    assert_eq!(output, b"x,y\n1,2\n");
+    ```
+    */
+    pub fn from_writer(writer: W) -> Self {
+        Self::from_writer_with(writer, &SaveOptions::default())
+    }
+
+    /**
+    Creates a writer to anything implementing [`std::io::Write`], using the given options for the csv dialect
+
+    ```
+    use delfi::{DatasetWriter, SaveOptions};
+
+    let options = SaveOptions::new().with_delimiter(b';');
+    let mut writer = DatasetWriter::from_writer_with(Vec::new(), &options).with_labels(["x", "y"]);
+    writer.write(&[1, 2]).unwrap();
+    let output = writer.finish().unwrap();
+    assert_eq!(output, b"x;y\n1;2\n");
+    ```
+    */
+    pub fn from_writer_with(writer: W, options: &SaveOptions) -> Self {
+        Self {
+            writer: CsvWriter::new(writer, options),
+            path: None,
+            labels: None,
+            flush_policy: FlushPolicy::default(),
+            // Without a header row there is nothing to write before the first datapoint
+            started: !options.header(),
+            existing: None,
+            temp: None,
+            n_datapoints: 0,
+            data: PhantomData,
+        }
+    }
+
+    /**
+    Take writer, set labels, and return writer.
+
+    The labels are written as a header row before the first datapoint,
+    so they have no effect if set after writing has started.
+    */
+    #[must_use]
+    pub fn with_labels<'a, Labels>(mut self, labels: Labels) -> Self
+    where
+        Labels: Into<Option<[&'a str; COLS]>>,
+    {
+        self.labels = labels.into().map(|labels| labels.map(ToOwned::to_owned));
+        self
+    }
+
+    /**
+    Take writer, set flush policy, and return writer
+    */
+    #[must_use]
+    pub fn with_flush_policy(mut self, flush_policy: FlushPolicy) -> Self {
+        self.flush_policy = flush_policy;
+        self
+    }
+
+    /**
+    Take writer, set the float format used for all columns without a format of their own, and return writer
+    */
+    #[must_use]
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.writer.float_formats_mut().set_default(format);
+        self
+    }
+
+    /**
+    Take writer, set the float format used for the given column, and return writer
+    */
+    #[must_use]
+    pub fn with_column_float_format(mut self, column: usize, format: FloatFormat) -> Self {
+        self.writer.float_formats_mut().set_column(column, format);
+        self
+    }
+
+    /**
+    Get current labels
+    */
+    #[must_use]
+    pub fn get_labels(&self) -> Option<&[String; COLS]> {
+        self.labels.as_ref()
+    }
+
+    /**
+    Get number of datapoints written so far
+    */
+    #[must_use]
+    pub fn n_datapoints(&self) -> usize {
+        self.n_datapoints
+    }
+
+    /**
+    Write a single datapoint, preceded by the header row if this is the first datapoint
+
+    # Errors
+    Returns an error if writing to the underlying writer failed,
+    or if the header row of the file being appended to does not match the labels
+    */
+    pub fn write(&mut self, datapoint: &Data) -> Result<(), Error> {
+        self.start()?;
+        self.writer
+            .write_datapoint(datapoint)
+            .map_err(|e| self.context(e))?;
+        self.n_datapoints += 1;
+
+        // The remainder is used since `usize::is_multiple_of` (and its lint) need a recent toolchain
+        #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+        let flush = match self.flush_policy {
+            FlushPolicy::Buffered => false,
+            FlushPolicy::EveryDatapoint => true,
+            FlushPolicy::Every(n) => n != 0 && self.n_datapoints % n == 0,
+        };
+        if flush {
+            self.writer.flush().map_err(|e| self.context(e))?;
+        }
+        Ok(())
+    }
+
+    /**
+    Flush everything written so far to the underlying writer
+
+    # Errors
+    Returns an error if writing to the underlying writer failed
+    */
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.start()?;
+        self.writer.flush().map_err(|e| self.context(e))
+    }
+
+    /**
+    Flush everything written and return the underlying writer
+
+    # Errors
+    Returns an error if writing to the underlying writer failed
+    */
+    pub fn finish(mut self) -> Result<W, Error> {
+        self.start()?;
+        let path = self.path.take();
+        let context = |error: Error| match &path {
+            Some(path) => error.at_path(path),
+            None => error,
+        };
+        let writer = self.writer.into_inner().map_err(context)?;
+        if let Some(temp) = self.temp.take() {
+            temp.commit().map_err(context)?;
+        }
+        Ok(writer)
+    }
+
+    /// Write the header row (or check the existing one), unless writing has already started
+    fn start(&mut self) -> Result<(), Error> {
+        if !self.started {
+            if let Some(first) = &self.existing {
+                let labels = self.labels.as_ref().filter(|_| self.writer.header());
+                output::check_existing(first, labels.map(|labels| &labels[..]), COLS)
+                    .map_err(|e| self.context(e))?;
+            } else if let Some(labels) = &self.labels {
+                self.writer
+                    .write_labels(labels)
+                    .map_err(|e| self.context(e))?;
+            }
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    /// Add the path of the file being written to the error, if any
+    fn context(&self, error: Error) -> Error {
+        match &self.path {
+            Some(path) => error.at_path(path),
+            None => error,
+        }
+    }
+}
+
+impl<const COLS: usize, Data: Labelled<COLS>, W: io::Write> DatasetWriter<COLS, Data, W> {
+    /**
+    Take writer, set labels to the labels of the datapoints, and return writer
+    */
+    #[must_use]
+    pub fn with_default_labels(self) -> Self {
+        self.with_labels(Data::LABELS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_once() {
+        let mut writer = DatasetWriter::from_writer(Vec::new()).with_labels(["a", "b"]);
+        writer.write(&[1, 2]).unwrap();
+        writer.write(&[3, 4]).unwrap();
+        assert_eq!(writer.n_datapoints(), 2);
+        let output = writer.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a,b\n1,2\n3,4\n");
+    }
+
+    #[test]
+    fn empty() {
+        let writer: DatasetWriter<2, [i32; 2], _> =
+            DatasetWriter::from_writer(Vec::new()).with_labels(["a", "b"]);
+        let output = writer.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a,b\n");
+    }
+
+    #[test]
+    fn float_format() {
+        let mut writer = DatasetWriter::from_writer(Vec::new())
+            .with_float_format(FloatFormat::Fixed(2))
+            .with_column_float_format(1, FloatFormat::RoundTrip);
+        writer.write(&[0.5, 0.25]).unwrap();
+        let output = writer.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "0.50,0.25\n");
+    }
+
+    #[test]
+    fn flush_policy() {
+        let mut writer =
+            DatasetWriter::from_writer(Vec::new()).with_flush_policy(FlushPolicy::EveryDatapoint);
+        writer.write(&("x", 1)).unwrap();
+        assert_eq!(writer.writer.get_ref(), b"x,1\n");
+    }
+}
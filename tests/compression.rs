@@ -0,0 +1,132 @@
+#![cfg(any(feature = "gzip", feature = "zstd", feature = "xz"))]
+
+use std::path::PathBuf;
+
+use delfi::{Compression, Dataset, DatasetWriter, SaveOptions};
+
+/**
+A directory for the files written by a test, which is removed when dropped.
+The files are kept out of the tree, as the compressed bytes depend on the enabled features.
+*/
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(test: &str) -> Self {
+        let name = format!("delfi-compression-{}-{test}", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn join(&self, filename: impl AsRef<std::path::Path>) -> PathBuf {
+        self.0.join(filename)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn codecs() -> Vec<Compression> {
+    vec![
+        #[cfg(feature = "gzip")]
+        Compression::Gzip,
+        #[cfg(feature = "zstd")]
+        Compression::Zstd,
+        #[cfg(feature = "xz")]
+        Compression::Xz,
+    ]
+}
+
+fn sample() -> Dataset<2, [u32; 2]> {
+    Dataset::from_columns([[0, 1, 2, 3], [0, 1, 4, 9]]).with_labels(["x", "y"])
+}
+
+#[test]
+fn save_and_load() {
+    let dir = TempDir::new("save_and_load");
+    for codec in codecs() {
+        let extension = codec.extension().unwrap();
+        let filepath = dir.join(format!("sample.csv.{extension}"));
+        sample().save(&filepath).unwrap();
+
+        // The file is compressed, rather than plain csv
+        let bytes = std::fs::read(&filepath).unwrap();
+        assert!(!bytes.starts_with(b"x,y"));
+
+        let loaded: Dataset<2, [u32; 2]> = Dataset::load(&filepath).unwrap();
+        assert_eq!(loaded.get_labels(), sample().get_labels());
+        assert_eq!(loaded.n_datapoints(), 4);
+    }
+}
+
+#[test]
+fn compression_level() {
+    let dir = TempDir::new("compression_level");
+    let dataset = Dataset::from_datapoints((0..1000).map(|i| [i, i % 7]));
+    for codec in codecs() {
+        let extension = codec.extension().unwrap();
+        let mut sizes = Vec::new();
+        for level in [0, 9] {
+            let filepath = dir.join(format!("level-{level}.csv.{extension}"));
+            let options = SaveOptions::new()
+                .with_header(false)
+                .with_compression_level(level);
+            dataset.save_with(&filepath, &options).unwrap();
+            sizes.push(std::fs::metadata(&filepath).unwrap().len());
+
+            let loaded: Dataset<2, [u32; 2]> = Dataset::load_with(&filepath, &options).unwrap();
+            assert_eq!(loaded.n_datapoints(), 1000);
+        }
+        assert!(sizes[1] <= sizes[0], "{codec:?}: {sizes:?}");
+    }
+}
+
+#[test]
+fn explicit_compression() {
+    let dir = TempDir::new("explicit_compression");
+    let codec = codecs()[0];
+    let filepath = dir.join("explicit.data");
+    let options = SaveOptions::new().with_compression(codec);
+    sample().save_with(&filepath, &options).unwrap();
+    let bytes = std::fs::read(&filepath).unwrap();
+    assert!(!bytes.starts_with(b"x,y"));
+
+    let extension = codec.extension().unwrap();
+    let filepath = dir.join(format!("explicit-plain.csv.{extension}"));
+    let options = SaveOptions::new().with_compression(Compression::None);
+    sample().save_with(&filepath, &options).unwrap();
+    let bytes = std::fs::read(&filepath).unwrap();
+    assert!(bytes.starts_with(b"x,y"));
+}
+
+#[test]
+fn append_compressed() {
+    let dir = TempDir::new("append_compressed");
+    for codec in codecs() {
+        let extension = codec.extension().unwrap();
+        let filepath = dir.join(format!("append.csv.{extension}"));
+        let _ = std::fs::remove_file(&filepath);
+        sample().append(&filepath).unwrap();
+        sample().append(&filepath).unwrap();
+
+        let mismatched = Dataset::from_datapoints([[1, 2]]).with_labels(["x", "z"]);
+        assert!(mismatched.append(&filepath).is_err());
+
+        let loaded: Dataset<2, [u32; 2]> = Dataset::load(&filepath).unwrap();
+        assert_eq!(loaded.get_labels(), sample().get_labels());
+        assert_eq!(loaded.n_datapoints(), 8);
+    }
+}
+
+#[test]
+fn writer_requires_plain() {
+    let dir = TempDir::new("writer_requires_plain");
+    let codec = codecs()[0];
+    let extension = codec.extension().unwrap();
+    let filepath = dir.join(format!("writer.csv.{extension}"));
+    let result: Result<DatasetWriter<2, [u32; 2]>, _> = DatasetWriter::create(&filepath);
+    assert!(result.is_err());
+}
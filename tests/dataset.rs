@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use delfi::{dataset, Dataset};
+use delfi::{dataset, Dataset, Error, Header, NonFinite, SaveOptions};
 
 const ROOT: &str = "./resources/data/tests/dataset";
 
@@ -45,3 +45,372 @@ fn macro_using_vectors() {
     let filepath = PathBuf::from(ROOT).join("macro-vectors.csv");
     dataset.save(filepath).unwrap();
 }
+
+#[test]
+fn content_using_arrays() {
+    let x = [1, 2, 3];
+    let y = [3, 4, 5];
+    let dataset = Dataset::from_columns([x, y]).with_labels(["x", "y"]);
+    assert_eq!(dataset.to_csv_string().unwrap(), "x,y\n1,3\n2,4\n3,5\n");
+}
+
+#[test]
+fn content_using_vectors() {
+    let t = vec![0.0, 1.0, 2.0];
+    let x = vec![0.5, 2.2, 6.3];
+    let dataset = dataset! {
+        "time" => t,
+        "length" => x,
+    };
+    let expected = "time,length\n0,0.5\n1,2.2\n2,6.3\n";
+    assert_eq!(dataset.to_csv_string().unwrap(), expected);
+}
+
+#[test]
+fn macro_datapoints_are_arrays() {
+    let c = [1, 2];
+    let dataset: Dataset<17, [i32; 17]> = dataset! {
+        array;
+        "c0" => c, "c1" => c, "c2" => c, "c3" => c, "c4" => c, "c5" => c,
+        "c6" => c, "c7" => c, "c8" => c, "c9" => c, "c10" => c, "c11" => c,
+        "c12" => c, "c13" => c, "c14" => c, "c15" => c, "c16" => c,
+    };
+    assert_eq!(dataset.n_datapoints(), 2);
+}
+
+#[test]
+fn save_snapshots() {
+    let mut dataset = Dataset::new().with_labels(["step", "value"]);
+    let filepath = PathBuf::from(ROOT).join("snapshots.csv");
+    for step in 0..10 {
+        dataset.push([step, 2 * step]);
+        if step % 5 == 4 {
+            dataset.save(&filepath).unwrap();
+        }
+    }
+    let loaded: Dataset<2, [i32; 2]> = Dataset::load(&filepath).unwrap();
+    assert_eq!(loaded.n_datapoints(), dataset.n_datapoints());
+}
+
+#[test]
+fn load_with_labels() {
+    let t = vec![0.0, 1.0, 2.0];
+    let x = vec![0.5, 2.2, 6.3];
+    let dataset = Dataset::from_columns([t, x]).with_labels(["time", "length"]);
+    let filepath = PathBuf::from(ROOT).join("load-labels.csv");
+    dataset.save(&filepath).unwrap();
+
+    let loaded: Dataset<2, [f64; 2]> = Dataset::load(&filepath).unwrap();
+    assert_eq!(loaded.get_labels(), dataset.get_labels());
+    assert_eq!(loaded.n_datapoints(), 3);
+}
+
+#[test]
+fn load_without_labels() {
+    let dataset = Dataset::from_datapoints([(1, 'a'), (2, 'b')]);
+    let filepath = PathBuf::from(ROOT).join("load-no-labels.csv");
+    let options = SaveOptions::new().with_header(false);
+    dataset.save_with(&filepath, &options).unwrap();
+
+    let loaded: Dataset<2, (u32, char)> = Dataset::load_with(&filepath, &options).unwrap();
+    assert_eq!(loaded.get_labels(), None);
+    assert_eq!(loaded.n_datapoints(), 2);
+
+    // Saving and loading with the default options detects that there is no header row
+    let dataset = Dataset::from_columns([[1, 2, 3], [4, 5, 6]]);
+    let filepath = PathBuf::from(ROOT).join("load-round-trip.csv");
+    dataset.save(&filepath).unwrap();
+    let loaded: Dataset<2, [u32; 2]> = Dataset::load(&filepath).unwrap();
+    assert_eq!(loaded.get_labels(), None);
+    assert_eq!(loaded.n_datapoints(), 3);
+    assert_eq!(loaded.to_csv_string().unwrap(), "1,4\n2,5\n3,6\n");
+}
+
+#[test]
+fn load_invalid() {
+    let dataset = Dataset::from_datapoints([["1", "2"], ["3", "four"]]).with_labels(["a", "b"]);
+    let filepath = PathBuf::from(ROOT).join("load-invalid.csv");
+    dataset.save(&filepath).unwrap();
+
+    let result: Result<Dataset<2, [u32; 2]>, _> = Dataset::load(&filepath);
+    match result {
+        Err(Error::Parse { path, row, source }) => {
+            assert_eq!(path, Some(filepath));
+            assert_eq!(row, 2);
+            assert_eq!(source.column(), 1);
+            assert_eq!(source.field(), Some("b"));
+            assert_eq!(source.text(), "four");
+        }
+        _ => panic!("Expected a parse error"),
+    }
+}
+
+#[test]
+fn load_invalid_first_row() {
+    // Without a header row the first row is data, and is not mistaken for labels
+    let csv = "1,x\n2,3\n";
+    let options = SaveOptions::new().with_header(false);
+    let result: Result<Dataset<2, [u32; 2]>, _> = Dataset::read_from_with(csv.as_bytes(), &options);
+    match result {
+        Err(Error::Parse { row, source, .. }) => {
+            assert_eq!(row, 0);
+            assert_eq!(source.column(), 1);
+            assert_eq!(source.text(), "x");
+        }
+        _ => panic!("Expected a parse error"),
+    }
+
+    // By default the first row is only a header row if it is not a datapoint
+    let dataset: Dataset<2, [u32; 2]> = Dataset::read_from("x,y\n2,3\n".as_bytes()).unwrap();
+    assert_eq!(dataset.get_labels().unwrap(), &["x", "y"]);
+    assert_eq!(dataset.n_datapoints(), 1);
+
+    let dataset: Dataset<2, [u32; 2]> = Dataset::read_from("1,2\n2,3\n".as_bytes()).unwrap();
+    assert_eq!(dataset.get_labels(), None);
+    assert_eq!(dataset.n_datapoints(), 2);
+
+    // A header row which is present is used as labels, even if it could be a datapoint
+    let options = SaveOptions::new().with_header(Header::Present);
+    let dataset: Dataset<2, [u32; 2]> =
+        Dataset::read_from_with("1,2\n2,3\n".as_bytes(), &options).unwrap();
+    assert_eq!(dataset.get_labels().unwrap(), &["1", "2"]);
+    assert_eq!(dataset.n_datapoints(), 1);
+}
+
+#[test]
+fn load_ragged() {
+    let csv = "1,2\n3\n";
+    let result: Result<Dataset<2, [u32; 2]>, _> = Dataset::read_from(csv.as_bytes());
+    match result {
+        Err(error @ Error::Shape { .. }) => {
+            assert_eq!(error.row(), Some(1));
+            assert_eq!(error.path(), None);
+        }
+        _ => panic!("Expected a shape error"),
+    }
+}
+
+#[cfg(not(feature = "xz"))]
+#[test]
+fn compression_not_enabled() {
+    let filepath = PathBuf::from(ROOT).join("not-enabled.csv.xz");
+    let dataset = Dataset::from_datapoints([[1, 2]]);
+    assert!(dataset.save(&filepath).is_err());
+    assert!(!filepath.exists());
+
+    let result: Result<Dataset<2, [u32; 2]>, _> = Dataset::load(&filepath);
+    match result {
+        Err(Error::Io { path, source }) => {
+            assert_eq!(path.as_deref(), Some(filepath.as_path()));
+            assert_eq!(source.kind(), std::io::ErrorKind::Unsupported);
+            assert!(source.to_string().contains("`xz` feature"), "{source}");
+        }
+        _ => panic!("Expected an io error"),
+    }
+}
+
+#[test]
+fn macro_strict() {
+    let t = vec![0.0, 1.0, 2.0];
+    let x = vec![0.5, 2.2, 6.3];
+    let dataset = dataset! {
+        strict;
+        "time" => t,
+        "length" => x,
+    }
+    .unwrap();
+    assert_eq!(dataset.n_rows(), 4);
+
+    let t = vec![0.0, 1.0, 2.0];
+    let x = vec![0.5, 2.2];
+    let result = dataset! {
+        strict;
+        "time" => t,
+        "length" => x,
+    };
+    assert!(matches!(result, Err(Error::ColumnLengths { .. })));
+
+    let steps = vec![0, 1, 2];
+    let phases = vec!["solid", "liquid"];
+    let result = dataset! {
+        strict;
+        "step" => steps,
+        "phase" => phases,
+    };
+    assert!(matches!(result, Err(Error::ColumnLengths { .. })));
+
+    let result: Result<Dataset<2, [f64; 2]>, _> = dataset! {
+        array;
+        strict;
+        "time" => vec![0.0, 1.0],
+        "length" => vec![0.5],
+    };
+    assert!(matches!(result, Err(Error::ColumnLengths { .. })));
+}
+
+#[test]
+fn macro_using_mixed_types() {
+    let steps_usize: Vec<usize> = vec![0, 1, 2];
+    let energies_f64: Vec<f64> = vec![1.5, 1.25, 1.125];
+    let names = ["solid", "liquid", "gas"];
+    let dataset = dataset! {"step" => steps_usize, "energy" => energies_f64, "phase" => names};
+    let expected = "step,energy,phase\n0,1.5,solid\n1,1.25,liquid\n2,1.125,gas\n";
+    assert_eq!(dataset.to_csv_string().unwrap(), expected);
+
+    let steps: Vec<usize> = vec![0, 1, 2];
+    let energies: Vec<f64> = vec![1.5, 1.25, 1.125];
+    let phases = ["solid", "liquid", "gas"];
+    let dataset = dataset! {
+        "step" => steps,
+        "energy" => energies,
+        "phase" => phases,
+    };
+    let expected = "step,energy,phase\n0,1.5,solid\n1,1.25,liquid\n2,1.125,gas\n";
+    assert_eq!(dataset.to_csv_string().unwrap(), expected);
+    let filepath = PathBuf::from(ROOT).join("macro-mixed.csv");
+    dataset.save(filepath).unwrap();
+}
+
+#[test]
+fn missing_values() {
+    let dataset = Dataset::from_datapoints([
+        (0, Some(1.5), Some("a")),
+        (1, None, Some("b")),
+        (2, Some(f64::NAN), None),
+    ])
+    .with_labels(["step", "value", "name"]);
+    let filepath = PathBuf::from(ROOT).join("missing.csv");
+    let options = SaveOptions::new()
+        .with_missing("NA")
+        .with_non_finite(NonFinite::Replace);
+    dataset.save_with(&filepath, &options).unwrap();
+
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "step,value,name\n0,1.5,a\n1,NA,b\n2,NA,NA\n");
+}
+
+#[test]
+fn reject_non_finite() {
+    let dataset =
+        Dataset::from_datapoints([[1.0, 2.0], [f64::INFINITY, 3.0]]).with_labels(["x", "y"]);
+    let filepath = PathBuf::from(ROOT).join("reject-non-finite.csv");
+    let options = SaveOptions::new().with_non_finite(NonFinite::Reject);
+    match dataset.save_with(&filepath, &options) {
+        Err(Error::NonFinite {
+            path,
+            row,
+            column,
+            value,
+        }) => {
+            assert_eq!(path, Some(filepath));
+            assert_eq!(row, 2);
+            assert_eq!(column, 0);
+            assert_eq!(value, "inf");
+        }
+        _ => panic!("Expected a non-finite error"),
+    }
+}
+
+#[test]
+fn append_to_file() {
+    let filepath = PathBuf::from(ROOT).join("append.csv");
+    let _ = std::fs::remove_file(&filepath);
+    let first = Dataset::from_datapoints([[1, 2], [3, 4]]).with_labels(["a", "b"]);
+    let second = Dataset::from_datapoints([[5, 6]]).with_labels(["a", "b"]);
+    first.append(&filepath).unwrap();
+    second.append(&filepath).unwrap();
+
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "a,b\n1,2\n3,4\n5,6\n");
+}
+
+#[test]
+fn append_mismatch() {
+    let filepath = PathBuf::from(ROOT).join("append-mismatch.csv");
+    let dataset = Dataset::from_datapoints([[1, 2]]).with_labels(["a", "b"]);
+    dataset.save(&filepath).unwrap();
+
+    let renamed = Dataset::from_datapoints([[3, 4]]).with_labels(["a", "c"]);
+    match renamed.append(&filepath) {
+        Err(Error::Labels {
+            path,
+            expected,
+            found,
+        }) => {
+            assert_eq!(path, Some(filepath.clone()));
+            assert_eq!(expected, ["a", "c"]);
+            assert_eq!(found, ["a", "b"]);
+        }
+        _ => panic!("Expected a labels error"),
+    }
+
+    let wider = Dataset::from_datapoints([[3, 4, 5]]);
+    match wider.append(&filepath) {
+        Err(Error::Shape {
+            expected, found, ..
+        }) => {
+            assert_eq!(expected, 3);
+            assert_eq!(found, 2);
+        }
+        _ => panic!("Expected a shape error"),
+    }
+
+    // Nothing is written when the header does not match
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "a,b\n1,2\n");
+}
+
+/// Whether any temporary files for the given file are left in its directory
+fn temp_files_left(filepath: &std::path::Path) -> bool {
+    let prefix = format!(".{}.", filepath.file_name().unwrap().to_str().unwrap());
+    std::fs::read_dir(filepath.parent().unwrap())
+        .unwrap()
+        .any(|entry| {
+            let name = entry.unwrap().file_name();
+            name.to_str().unwrap().starts_with(&prefix)
+        })
+}
+
+#[test]
+fn save_atomic() {
+    let filepath = PathBuf::from(ROOT).join("atomic.csv");
+    let options = SaveOptions::new().with_atomic(true);
+    let first = Dataset::from_datapoints([[1, 2], [3, 4]]).with_labels(["a", "b"]);
+    first.save_with(&filepath, &options).unwrap();
+    let second = Dataset::from_datapoints([[5, 6]]).with_labels(["a", "b"]);
+    second.save_with(&filepath, &options).unwrap();
+
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "a,b\n5,6\n");
+    assert!(!temp_files_left(&filepath));
+
+    first.append_with(&filepath, &options).unwrap();
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "a,b\n5,6\n1,2\n3,4\n");
+    assert!(!temp_files_left(&filepath));
+
+    // A failed save leaves the existing file as it was
+    let options = options.with_non_finite(NonFinite::Reject);
+    let invalid = Dataset::from_datapoints([[f64::NAN, 1.0]]).with_labels(["a", "b"]);
+    assert!(invalid.save_with(&filepath, &options).is_err());
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "a,b\n5,6\n1,2\n3,4\n");
+    assert!(!temp_files_left(&filepath));
+}
+
+#[cfg(unix)]
+#[test]
+fn save_atomic_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let filepath = PathBuf::from(ROOT).join("atomic-permissions.csv");
+    let dataset = Dataset::from_datapoints([[1, 2]]).with_labels(["a", "b"]);
+    dataset.save(&filepath).unwrap();
+    let permissions = std::fs::Permissions::from_mode(0o600);
+    std::fs::set_permissions(&filepath, permissions).unwrap();
+
+    let options = SaveOptions::new().with_atomic(true);
+    dataset.save_with(&filepath, &options).unwrap();
+    let mode = std::fs::metadata(&filepath).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use delfi::field::RecordBuf;
+use delfi::{Datapoint, Dataset, Error, FloatFormat, JsonLayout};
+
+const ROOT: &str = "./resources/data/tests/json";
+
+fn json(dataset: &Dataset<3, (u32, Option<f64>, &str)>, layout: JsonLayout) -> String {
+    let mut buffer = Vec::new();
+    dataset.write_json_to(&mut buffer, layout).unwrap();
+    String::from_utf8(buffer).unwrap()
+}
+
+fn sample() -> Dataset<3, (u32, Option<f64>, &'static str)> {
+    Dataset::from_datapoints([
+        (0, Some(1.5), "a"),
+        (1, None, "say \"hi\""),
+        (2, Some(f64::NAN), "12"),
+    ])
+}
+
+#[test]
+fn records() {
+    let dataset = sample().with_labels(["step", "value", "note"]);
+    let expected = concat!(
+        "[\n",
+        "  {\"step\":0,\"value\":1.5,\"note\":\"a\"},\n",
+        "  {\"step\":1,\"value\":null,\"note\":\"say \\\"hi\\\"\"},\n",
+        "  {\"step\":2,\"value\":null,\"note\":12}\n",
+        "]\n",
+    );
+    assert_eq!(json(&dataset, JsonLayout::Records), expected);
+    dataset
+        .save_json(
+            PathBuf::from(ROOT).join("records.json"),
+            JsonLayout::Records,
+        )
+        .unwrap();
+
+    let unlabelled = sample();
+    let expected = "[\n  [0,1.5,\"a\"],\n  [1,null,\"say \\\"hi\\\"\"],\n  [2,null,12]\n]\n";
+    assert_eq!(json(&unlabelled, JsonLayout::Records), expected);
+}
+
+#[test]
+fn columns() {
+    let dataset = sample().with_labels(["step", "value", "note"]);
+    let expected = concat!(
+        "{\n",
+        "  \"step\":[0,1,2],\n",
+        "  \"value\":[1.5,null,null],\n",
+        "  \"note\":[\"a\",\"say \\\"hi\\\"\",12]\n",
+        "}\n",
+    );
+    assert_eq!(json(&dataset, JsonLayout::Columns), expected);
+    dataset
+        .save_json(
+            PathBuf::from(ROOT).join("columns.json"),
+            JsonLayout::Columns,
+        )
+        .unwrap();
+
+    let unlabelled = sample();
+    let expected = "[\n  [0,1,2],\n  [1.5,null,null],\n  [\"a\",\"say \\\"hi\\\"\",12]\n]\n";
+    assert_eq!(json(&unlabelled, JsonLayout::Columns), expected);
+
+    let empty: Dataset<3, (u32, Option<f64>, &str)> = Dataset::new();
+    assert_eq!(json(&empty, JsonLayout::Records), "[]\n");
+    assert_eq!(
+        json(&empty, JsonLayout::Columns),
+        "[\n  [],\n  [],\n  []\n]\n"
+    );
+}
+
+#[test]
+fn lines() {
+    let dataset = Dataset::from_datapoints([[0.25, 1e-7], [3.0, -2.0]])
+        .with_labels(["a", "b"])
+        .with_float_format(FloatFormat::Scientific(1));
+    let filepath = PathBuf::from(ROOT).join("lines.jsonl");
+    dataset.save_jsonl(&filepath).unwrap();
+
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(
+        contents,
+        "{\"a\":2.5e-1,\"b\":1.0e-7}\n{\"a\":3.0e0,\"b\":-2.0e0}\n"
+    );
+}
+
+#[test]
+fn empty_strings_and_missing_values() {
+    let dataset = Dataset::from_datapoints([(Some(""), ""), (None, "x")]).with_labels(["a", "b"]);
+    let mut buffer = Vec::new();
+    dataset.write_jsonl_to(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "{\"a\":\"\",\"b\":\"\"}\n{\"a\":null,\"b\":\"x\"}\n"
+    );
+}
+
+#[test]
+fn bools() {
+    let dataset = Dataset::from_datapoints([(true, "true"), (false, "no")]);
+    let mut buffer = Vec::new();
+    dataset
+        .write_json_to(&mut buffer, JsonLayout::Columns)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        "[\n  [true,false],\n  [\"true\",\"no\"]\n]\n"
+    );
+}
+
+/// A datapoint which writes more fields than it claims to have
+struct Ragged;
+
+impl Datapoint<2> for Ragged {
+    fn record(&self) -> [String; 2] {
+        [String::from("a"), String::from("b")]
+    }
+
+    fn write_record(&self, record: &mut RecordBuf) {
+        for field in ["a", "b", "c"] {
+            record.push_str(field);
+        }
+    }
+}
+
+#[test]
+fn ragged_datapoints() {
+    let dataset = Dataset::from_datapoints([Ragged]).with_labels(["x", "y"]);
+    for layout in [JsonLayout::Records, JsonLayout::Columns] {
+        let result = dataset.write_json_to(Vec::new(), layout);
+        assert!(
+            matches!(
+                result,
+                Err(Error::Shape {
+                    row: 0,
+                    expected: 2,
+                    found: 3,
+                    ..
+                })
+            ),
+            "{result:?}"
+        );
+    }
+    assert!(dataset.write_jsonl_to(Vec::new()).is_err());
+}
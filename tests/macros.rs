@@ -1,20 +1,160 @@
 #![cfg(feature = "macros")]
+// The value written is arbitrary, and is not meant as pi
+#![allow(clippy::approx_constant)]
 
+use std::fmt;
 use std::path::PathBuf;
 
-use delfi::{Datapoint, Dataset};
+use delfi::{
+    field::Displayed, Datapoint, Dataset, DatasetWriter, DynDataset, FloatFormat, FromRecord,
+    Labelled, Nested, SaveOptions,
+};
 
 const ROOT: &str = "./resources/data/tests/macros";
 
-#[derive(Debug, Datapoint)]
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
 struct Count {
     ch: char,
     i: usize,
 }
 
-#[derive(Debug, Datapoint)]
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
 struct Point(f64, f64);
 
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
+struct Sample {
+    #[delfi(rename = "value [m]")]
+    value: f64,
+    #[delfi(skip)]
+    cache: Option<usize>,
+    #[delfi(order = 0, rename = "time [s]")]
+    time: f64,
+    step: usize,
+}
+
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
+struct Generic<T> {
+    t: f64,
+    value: T,
+}
+
+#[derive(Debug, Datapoint)]
+struct Borrowed<'a, T: Copy>(&'a str, T);
+
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
+struct Bounded<T, C>
+where
+    T: Copy,
+{
+    x: T,
+    #[delfi(skip)]
+    cache: Vec<C>,
+}
+
+#[derive(Debug, Clone, Copy, Datapoint)]
+enum Phase {
+    Solid,
+    #[delfi(rename = "fluid")]
+    Liquid,
+    Gas,
+}
+
+#[derive(Debug, Datapoint)]
+enum Event<T> {
+    Collision {
+        pos: T,
+        #[delfi(rename = "energy [J]")]
+        energy: f64,
+    },
+    Spawn {
+        id: u32,
+        pos: T,
+        #[delfi(skip)]
+        _handle: Option<Box<Event<T>>>,
+    },
+    Tick(u64),
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, Datapoint)]
+struct Vec3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+#[derive(Debug, Datapoint)]
+struct Body {
+    t: f64,
+    #[delfi(flatten)]
+    pos: Vec3,
+    #[delfi(flatten, rename = "v")]
+    velocity: [f64; 3],
+    mass: f64,
+}
+
+#[derive(Debug, Datapoint)]
+struct System(u32, #[delfi(flatten)] Body);
+
+#[derive(Debug, PartialEq, Datapoint, FromRecord)]
+struct Reordered(#[delfi(skip)] String, f64, #[delfi(order = 0)] u8);
+
+#[derive(Datapoint)]
+struct Observation {
+    #[delfi(format = "{:.6e}")]
+    t: f64,
+    #[delfi(with = "degrees", rename = "angle [deg]")]
+    angle: f64,
+    #[delfi(format = "{:>4}")]
+    id: u32,
+}
+
+#[derive(Datapoint)]
+enum Reading {
+    Angle {
+        #[delfi(with = "degrees")]
+        angle: f64,
+    },
+    Level(#[delfi(format = "{:.1}")] f32),
+}
+
+#[derive(Datapoint)]
+struct Partial {
+    id: u32,
+    value: Option<f64>,
+    note: Option<String>,
+}
+
+/// A type which only implements `Display`, and not `Field`
+#[derive(Debug, Clone, Copy)]
+struct Celsius(f64);
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} C", self.0)
+    }
+}
+
+#[derive(Datapoint)]
+struct Weather {
+    place: &'static str,
+    temperature: Celsius,
+    #[delfi(flatten)]
+    range: [Celsius; 2],
+}
+
+#[derive(Datapoint)]
+enum Forecast {
+    Known(Celsius),
+    Unknown,
+}
+
+// Functions used by the `with` attribute take a reference to the field
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn degrees(radians: &f64) -> f64 {
+    radians.to_degrees()
+}
+
 #[test]
 fn derive_named() {
     let a = Count { ch: 'a', i: 49 };
@@ -73,3 +213,313 @@ fn dataset_from_unnamed() {
     let filepath = PathBuf::from(ROOT).join("dataset-unnamed.csv");
     dataset.save(filepath).unwrap();
 }
+
+#[test]
+fn parse_named() {
+    let record = [String::from("a"), String::from("49")];
+    let count = Count::from_record(record).unwrap();
+    assert_eq!(count, Count { ch: 'a', i: 49 });
+}
+
+#[test]
+fn parse_unnamed() {
+    let record = csv::StringRecord::from(vec!["3", "4.5"]);
+    let point = Point::from_string_record(&record).unwrap();
+    assert_eq!(point, Point(3.0, 4.5));
+}
+
+#[test]
+fn parse_error() {
+    let record = [String::from("a"), String::from("-1")];
+    let error = Count::from_record(record).unwrap_err();
+    assert_eq!(error.column(), 1);
+    assert_eq!(error.field(), Some("i"));
+    assert_eq!(error.text(), "-1");
+}
+
+#[test]
+fn dataset_load_derived() {
+    let mut dataset = Dataset::new().with_labels(["ch", "i"]);
+    dataset.push(Count { ch: 'x', i: 1 });
+    dataset.push(Count { ch: 'y', i: 2 });
+
+    let filepath = PathBuf::from(ROOT).join("dataset-load.csv");
+    dataset.save(&filepath).unwrap();
+
+    let loaded: Dataset<2, Count> = Dataset::load(&filepath).unwrap();
+    assert_eq!(loaded.n_datapoints(), 2);
+    assert_eq!(
+        loaded.get_labels(),
+        Some(&[String::from("ch"), String::from("i")])
+    );
+}
+
+#[test]
+fn dyn_dataset_from_derived() {
+    let mut dataset = Dataset::new().with_labels(["x", "y"]);
+    dataset.push(Point(1.0, 2.0));
+    dataset.push(Point(3.0, 4.5));
+    let dataset = DynDataset::from(dataset);
+    assert_eq!(dataset.n_columns(), 2);
+    assert_eq!(dataset.to_csv_string().unwrap(), "x,y\n1,2\n3,4.5\n");
+}
+
+#[test]
+fn float_format_derived() {
+    let mut dataset = Dataset::new()
+        .with_labels(["x", "y"])
+        .with_float_format(FloatFormat::Fixed(3))
+        .with_column_float_format(1, FloatFormat::Significant(2));
+    dataset.push(Point(0.1 + 0.2, 12_345.0));
+    dataset.push(Point(1.0 / 3.0, 0.000_678_9));
+
+    let filepath = PathBuf::from(ROOT).join("float-format.csv");
+    dataset.save(&filepath).unwrap();
+    assert_eq!(
+        dataset.to_csv_string().unwrap(),
+        "x,y\n0.300,12000\n0.333,0.00068\n"
+    );
+}
+
+#[test]
+fn attributes() {
+    let sample = Sample {
+        value: 2.5,
+        cache: Some(3),
+        time: 0.5,
+        step: 7,
+    };
+    let record: [String; 3] = sample.record();
+    assert_eq!(record, ["0.5", "2.5", "7"]);
+
+    let parsed = Sample::from_record(record).unwrap();
+    assert_eq!(parsed.cache, None);
+    assert!((parsed.time - 0.5).abs() < f64::EPSILON);
+
+    let record = [String::from("1"), String::from("x"), String::from("2")];
+    let error = Sample::from_record(record).unwrap_err();
+    assert_eq!(error.column(), 1);
+    assert_eq!(error.field(), Some("value [m]"));
+}
+
+#[test]
+fn attributes_unnamed() {
+    let reordered = Reordered(String::from("cache"), 1.5, 3);
+    let record: [String; 2] = reordered.record();
+    assert_eq!(record, ["3", "1.5"]);
+    let parsed = Reordered::from_record(record).unwrap();
+    assert_eq!(parsed, Reordered(String::new(), 1.5, 3));
+}
+
+#[test]
+fn labels() {
+    assert_eq!(Count::LABELS, ["ch", "i"]);
+    assert_eq!(Point::LABELS, ["0", "1"]);
+    assert_eq!(Sample::LABELS, ["time [s]", "value [m]", "step"]);
+    assert_eq!(Reordered::LABELS, ["2", "1"]);
+}
+
+#[test]
+fn dataset_with_default_labels() {
+    let dataset = Dataset::from_labelled_datapoints([Count { ch: 'x', i: 1 }]);
+    let filepath = PathBuf::from(ROOT).join("default-labels.csv");
+    dataset.save(&filepath).unwrap();
+    assert_eq!(dataset.to_csv_string().unwrap(), "ch,i\nx,1\n");
+
+    let mut dataset = Dataset::new().with_default_labels();
+    dataset.push(Point(1.0, 2.0));
+    assert_eq!(dataset.to_csv_string().unwrap(), "0,1\n1,2\n");
+}
+
+#[test]
+fn writer_with_default_labels() {
+    let mut writer = DatasetWriter::from_writer(Vec::new()).with_default_labels();
+    writer.write(&Count { ch: 'y', i: 2 }).unwrap();
+    let output = writer.finish().unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "ch,i\ny,2\n");
+}
+
+#[test]
+fn generics() {
+    let single = Generic {
+        t: 0.5,
+        value: 1.5_f32,
+    };
+    let double = Generic {
+        t: 0.5,
+        value: 1.5_f64,
+    };
+    assert_eq!(single.record(), double.record());
+    assert_eq!(Generic::<f32>::LABELS, ["t", "value"]);
+
+    let record = [String::from("1"), String::from("2")];
+    let parsed: Generic<u8> = Generic::from_record(record).unwrap();
+    assert_eq!(parsed, Generic { t: 1.0, value: 2 });
+
+    let name = String::from("a");
+    let borrowed = Borrowed(&name, 3);
+    assert_eq!(borrowed.record(), ["a", "3"]);
+
+    let bounded: Bounded<i32, String> = Bounded {
+        x: -1,
+        cache: vec![String::new()],
+    };
+    let record: [String; 1] = bounded.record();
+    let parsed: Bounded<i32, String> = Bounded::from_record(record).unwrap();
+    assert_eq!(parsed.cache, Vec::<String>::new());
+
+    let dataset = Dataset::from_labelled_datapoints([single]);
+    assert_eq!(dataset.to_csv_string().unwrap(), "t,value\n0.5,1.5\n");
+}
+
+#[test]
+fn fieldless_enum() {
+    assert_eq!(Phase::LABELS, ["Phase"]);
+    let phases = [Phase::Solid, Phase::Liquid, Phase::Gas];
+    let records: Vec<[String; 1]> = phases.iter().map(Datapoint::record).collect();
+    assert_eq!(records, [["Solid"], ["fluid"], ["Gas"]]);
+
+    let dataset = Dataset::from_labelled_datapoints(phases);
+    assert_eq!(
+        dataset.to_csv_string().unwrap(),
+        "Phase\nSolid\nfluid\nGas\n"
+    );
+}
+
+#[test]
+fn data_enum() {
+    assert_eq!(
+        Event::<f64>::LABELS,
+        ["Event", "pos", "energy [J]", "id", "0"]
+    );
+
+    let events = [
+        Event::Collision {
+            pos: 1.5,
+            energy: 2.0,
+        },
+        Event::Spawn {
+            id: 7,
+            pos: 0.5,
+            _handle: None,
+        },
+        Event::Tick(3),
+        Event::Stop,
+    ];
+    let dataset = Dataset::from_labelled_datapoints(events);
+    let filepath = PathBuf::from(ROOT).join("enum.csv");
+    dataset.save(&filepath).unwrap();
+    assert_eq!(
+        dataset.to_csv_string().unwrap(),
+        "Event,pos,energy [J],id,0\n\
+         Collision,1.5,2,,\n\
+         Spawn,0.5,,7,\n\
+         Tick,,,,3\n\
+         Stop,,,,\n"
+    );
+}
+
+#[test]
+fn flatten() {
+    assert_eq!(Vec3::WIDTH, 3);
+    assert_eq!(
+        Body::LABELS,
+        ["t", "pos.x", "pos.y", "pos.z", "v.0", "v.1", "v.2", "mass"]
+    );
+    assert_eq!(System::LABELS[0], "0");
+    assert_eq!(System::LABELS[2], "1.pos.x");
+
+    let body = Body {
+        t: 0.5,
+        pos: Vec3 {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+        },
+        velocity: [0.0, -1.0, 0.5],
+        mass: 10.0,
+    };
+    let record: [String; 8] = body.record();
+    assert_eq!(record, ["0.5", "1", "2", "3", "0", "-1", "0.5", "10"]);
+
+    let dataset = Dataset::from_labelled_datapoints([System(1, body)]);
+    let filepath = PathBuf::from(ROOT).join("flatten.csv");
+    dataset.save(&filepath).unwrap();
+    assert_eq!(dataset.n_columns(), 9);
+}
+
+#[test]
+fn custom_format() {
+    let observation = Observation {
+        t: 1234.5,
+        angle: std::f64::consts::PI,
+        id: 7,
+    };
+    let record: [String; 3] = observation.record();
+    assert_eq!(record, ["1.234500e3", "180", "   7"]);
+    assert_eq!(Observation::LABELS, ["t", "angle [deg]", "id"]);
+
+    let readings = [Reading::Angle { angle: 0.0 }, Reading::Level(2.25)];
+    let records: Vec<[String; 3]> = readings.iter().map(Datapoint::record).collect();
+    assert_eq!(records[0], ["Angle", "0", ""]);
+    assert_eq!(records[1], ["Level", "", "2.2"]);
+
+    let dataset = Dataset::from_labelled_datapoints([observation]);
+    let filepath = PathBuf::from(ROOT).join("custom-format.csv");
+    dataset.save(&filepath).unwrap();
+}
+
+#[test]
+fn optional_fields() {
+    let rows = [
+        Partial {
+            id: 0,
+            value: Some(0.5),
+            note: None,
+        },
+        Partial {
+            id: 1,
+            value: None,
+            note: Some(String::from("gap")),
+        },
+    ];
+    let dataset = Dataset::from_labelled_datapoints(rows);
+    let options = SaveOptions::new().with_missing("null");
+    let mut output = Vec::new();
+    dataset.write_to_with(&mut output, &options).unwrap();
+    assert_eq!(output, b"id,value,note\n0,0.5,null\n1,null,gap\n");
+
+    // Fields absent from a variant are missing as well
+    let dataset = Dataset::from_datapoints([Forecast::Unknown]);
+    let mut output = Vec::new();
+    dataset.write_to_with(&mut output, &options).unwrap();
+    assert_eq!(output, b"Unknown,null\n");
+}
+
+#[test]
+fn display_only_fields() {
+    let freezing = Celsius(0.0);
+    let warm = Celsius(21.5);
+
+    let arrays = Dataset::from_datapoints([[Displayed(freezing), Displayed(warm)]]);
+    assert_eq!(arrays.to_csv_string().unwrap(), "0 C,21.5 C\n");
+
+    let tuples = Dataset::from_datapoints([("Oslo", Displayed(warm))]);
+    assert_eq!(tuples.to_csv_string().unwrap(), "Oslo,21.5 C\n");
+
+    let columns = Dataset::from_columns([[freezing, warm].map(Displayed)]);
+    assert_eq!(columns.to_csv_string().unwrap(), "0 C\n21.5 C\n");
+
+    let weather = Weather {
+        place: "Oslo",
+        temperature: warm,
+        range: [freezing, warm],
+    };
+    let derived = Dataset::from_labelled_datapoints([weather]);
+    assert_eq!(
+        derived.to_csv_string().unwrap(),
+        "place,temperature,range.0,range.1\nOslo,21.5 C,0 C,21.5 C\n"
+    );
+    assert_eq!(Forecast::Known(warm).record(), ["Known", "21.5 C"]);
+    assert_eq!(Forecast::Unknown.record(), ["Unknown", ""]);
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use delfi::{dataset, Dataset};
+use delfi::{dataset, Dataset, FloatFormat};
 use ndarray::Array;
 
 const ROOT: &str = "./resources/data/tests/ndarray";
@@ -17,6 +17,21 @@ fn columns_using_arrays() {
     dataset.save(filepath).unwrap();
 }
 
+#[test]
+fn columns_with_float_format() {
+    const N: usize = 1000;
+    let t = Array::linspace(0., 10., N + 1);
+    let x = Array::logspace(10., 0., 2., N + 1);
+
+    let dataset = Dataset::from_columns([t, x])
+        .with_labels(["time", "length"])
+        .with_float_format(FloatFormat::Scientific(6))
+        .with_column_float_format(0, FloatFormat::Fixed(2));
+
+    let filepath = PathBuf::from(ROOT).join("columns-float-format.csv");
+    dataset.save(filepath).unwrap();
+}
+
 #[test]
 fn macro_using_arrays() {
     const N: usize = 1000;
@@ -0,0 +1,7 @@
+#![cfg(feature = "macros")]
+
+#[test]
+fn derive_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
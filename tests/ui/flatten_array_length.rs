@@ -0,0 +1,11 @@
+use delfi::Datapoint;
+
+const DIM: usize = 3;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(flatten)]
+    pos: [f64; DIM],
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Body<P> {
+    #[delfi(flatten)]
+    pos: P,
+    mass: f64,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+enum Event {
+    Move {
+        #[delfi(flatten)]
+        to: [f64; 2],
+    },
+}
+
+fn main() {}
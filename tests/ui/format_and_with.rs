@@ -0,0 +1,9 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(format = "{:.2}", with = "str::len")]
+    x: f64,
+}
+
+fn main() {}
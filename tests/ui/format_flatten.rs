@@ -0,0 +1,9 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(flatten, format = "{:?}")]
+    x: [f64; 2],
+}
+
+fn main() {}
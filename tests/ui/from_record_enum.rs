@@ -0,0 +1,9 @@
+use delfi::FromRecord;
+
+#[derive(FromRecord)]
+enum Phase {
+    Solid,
+    Liquid,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use delfi::{Datapoint, FromRecord};
+
+#[derive(Datapoint, FromRecord)]
+struct Sample {
+    #[delfi(flatten)]
+    pos: [f64; 2],
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use delfi::FromRecord;
+
+#[derive(FromRecord)]
+struct Marker;
+
+fn main() {}
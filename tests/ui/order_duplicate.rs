@@ -0,0 +1,11 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(order = 0)]
+    x: f64,
+    #[delfi(order = 0)]
+    y: f64,
+}
+
+fn main() {}
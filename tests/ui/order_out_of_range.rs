@@ -0,0 +1,10 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(order = 2)]
+    x: f64,
+    y: f64,
+}
+
+fn main() {}
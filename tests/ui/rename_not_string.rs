@@ -0,0 +1,9 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(rename = 3)]
+    x: f64,
+}
+
+fn main() {}
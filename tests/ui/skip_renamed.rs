@@ -0,0 +1,10 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(skip, rename = "x")]
+    x: f64,
+    y: f64,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+enum Phase {
+    Solid,
+    #[delfi(skip)]
+    Liquid,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
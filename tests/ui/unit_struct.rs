@@ -0,0 +1,6 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Marker;
+
+fn main() {}
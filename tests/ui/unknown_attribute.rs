@@ -0,0 +1,9 @@
+use delfi::Datapoint;
+
+#[derive(Datapoint)]
+struct Sample {
+    #[delfi(hide)]
+    x: f64,
+}
+
+fn main() {}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use delfi::{Dataset, DatasetWriter, Error, FlushPolicy, SaveOptions};
+
+const ROOT: &str = "./resources/data/tests/writer";
+
+#[test]
+fn stream_to_file() {
+    let filepath = PathBuf::from(ROOT).join("stream.csv");
+    let mut writer = DatasetWriter::create(&filepath)
+        .unwrap()
+        .with_labels(["step", "value"]);
+    for step in 0..10 {
+        writer.write(&[step, step * step]).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let loaded: Dataset<2, [i32; 2]> = Dataset::load(&filepath).unwrap();
+    assert_eq!(loaded.n_datapoints(), 10);
+    assert_eq!(
+        loaded.get_labels(),
+        Some(&[String::from("step"), String::from("value")])
+    );
+}
+
+#[test]
+fn flush_every() {
+    let filepath = PathBuf::from(ROOT).join("flush-every.csv");
+    let mut writer = DatasetWriter::create(&filepath)
+        .unwrap()
+        .with_flush_policy(FlushPolicy::Every(2));
+    writer.write(&(0.5, "a")).unwrap();
+    writer.write(&(1.5, "b")).unwrap();
+
+    // The first two datapoints are on disk before the writer is finished
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "0.5,a\n1.5,b\n");
+
+    writer.write(&(2.5, "c")).unwrap();
+    writer.finish().unwrap();
+}
+
+#[test]
+fn append_to_file() {
+    let filepath = PathBuf::from(ROOT).join("append.csv");
+    let _ = std::fs::remove_file(&filepath);
+    for run in 0..3 {
+        let mut writer = DatasetWriter::append(&filepath)
+            .unwrap()
+            .with_labels(["run", "value"]);
+        writer.write(&[run, 2 * run]).unwrap();
+        writer.finish().unwrap();
+    }
+    let contents = std::fs::read_to_string(&filepath).unwrap();
+    assert_eq!(contents, "run,value\n0,0\n1,2\n2,4\n");
+
+    let mut writer = DatasetWriter::append(&filepath)
+        .unwrap()
+        .with_labels(["run", "other"]);
+    let result = writer.write(&[3, 6]);
+    assert!(matches!(result, Err(Error::Labels { path: Some(_), .. })));
+}
+
+#[test]
+fn create_atomic() {
+    let filepath = PathBuf::from(ROOT).join("atomic.csv");
+    std::fs::write(&filepath, "old\n").unwrap();
+    let options = SaveOptions::new().with_atomic(true);
+
+    let mut writer = DatasetWriter::create_with(&filepath, &options)
+        .unwrap()
+        .with_flush_policy(FlushPolicy::EveryDatapoint);
+    writer.write(&[1, 2]).unwrap();
+    // Nothing is replaced until the writer is finished
+    assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "old\n");
+    writer.finish().unwrap();
+    assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "1,2\n");
+
+    // Dropping the writer without finishing it leaves the file as it was
+    let mut writer = DatasetWriter::create_with(&filepath, &options).unwrap();
+    writer.write(&[3, 4]).unwrap();
+    drop(writer);
+    assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "1,2\n");
+}